@@ -0,0 +1,66 @@
+use std::hint::black_box;
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use diceware::{builtin_wordlist, builtin_wordlist_ref, Passphraser};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn builtin(c: &mut Criterion) {
+  c.bench_function("builtin_wordlist", |b| {
+    b.iter(|| black_box(builtin_wordlist()))
+  });
+
+  c.bench_function("builtin_wordlist_ref", |b| {
+    b.iter(|| black_box(builtin_wordlist_ref()))
+  });
+
+  c.bench_function("Passphraser::new", |b| {
+    b.iter(|| black_box(Passphraser::new(6)))
+  });
+}
+
+fn wordlist(c: &mut Criterion) {
+  let wordlist = (0..100_000).map(|n| format!("word{n}")).collect::<Vec<_>>();
+
+  let shared = Arc::<[String]>::from(wordlist.as_slice());
+
+  c.bench_function("Passphraser::wordlist", |b| {
+    b.iter(|| black_box(Passphraser::new(6).wordlist(&wordlist).generate()))
+  });
+
+  c.bench_function("Passphraser::wordlist_shared", |b| {
+    b.iter(|| {
+      black_box(
+        Passphraser::new(6)
+          .wordlist_shared(shared.clone())
+          .generate(),
+      )
+    })
+  });
+}
+
+fn batch(c: &mut Criterion) {
+  let builder = Passphraser::new(6);
+  let mut rng = StdRng::seed_from_u64(42);
+  let mut out = Vec::with_capacity(1000);
+
+  c.bench_function("Passphraser::generate_with x1000", |b| {
+    b.iter(|| {
+      for _ in 0..1000 {
+        black_box(builder.generate_with(&mut rng));
+      }
+    })
+  });
+
+  c.bench_function("Passphraser::generate_batch_into x1000", |b| {
+    b.iter(|| {
+      out.clear();
+      builder.generate_batch_into(&mut out, 1000, &mut rng);
+      black_box(&out);
+    })
+  });
+}
+
+criterion_group!(benches, builtin, wordlist, batch);
+criterion_main!(benches);
@@ -1,29 +1,473 @@
-use clap::Parser;
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Display;
+
+use clap::builder::{PossibleValuesParser, RangedU64ValueParser, TypedValueParser};
+use clap::error::{Error, ErrorKind};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use clap_complete::Shell;
+use diceware::{Passphraser, Preset};
 
 #[derive(Parser, Debug)]
 #[clap(version, about = "Generates strong Diceware passphrases.", long_about = None)]
 pub struct Cli {
-  /// How much words to generate.
-  #[arg(short, long, default_value_t = 6)]
+  /// How much words to generate, from 1 to 64. Falls back to `DICEWARE_LENGTH`.
+  #[arg(short, long, default_value_t = Passphraser::LENGTH_DEFAULT, value_parser = parse_length)]
   pub length: usize,
 
-  /// Path to a custom wordlist.
+  /// Minimum entropy in bits, used to calculate how much words to generate.
+  #[arg(
+    short,
+    long,
+    value_name = "BITS",
+    visible_alias = "target-bits",
+    conflicts_with = "length",
+    value_parser = parse_min_entropy
+  )]
+  pub min_entropy: Option<f32>,
+
+  /// Minimum number of chars, adding words until the passphrase is long enough.
+  #[arg(long, value_name = "CHARS", conflicts_with = "rolls")]
+  pub min_chars: Option<usize>,
+
+  /// Path to a custom wordlist, or `-` to read it from stdin. Can be used multiple times to
+  /// concatenate wordlists, which must not share indices.
   #[arg(short, long)]
-  pub wordlist: Option<String>,
+  pub wordlist: Vec<String>,
+
+  /// Exclude words containing the given substring. Can be used multiple times.
+  #[arg(short = 'x', long, value_name = "SUBSTRING")]
+  pub exclude: Vec<String>,
+
+  /// Path to a list of banned words, one per line, e.g. common passwords. Banned words are
+  /// re-rolled, slightly reducing entropy.
+  #[arg(long, value_name = "PATH")]
+  pub banlist: Option<String>,
+
+  /// Use given dice rolls instead of random ones, e.g. "11111 23456" or "1-1-1-1-1,2-3-4-5-6".
+  #[arg(long, conflicts_with_all = ["length", "min_entropy", "exclude"])]
+  pub rolls: Option<String>,
 
   /// Show entropy of the passphrase.
   #[arg(short, long)]
   pub entropy: bool,
 
+  /// Show the first letters of words to help memorizing the passphrase.
+  #[arg(short, long)]
+  pub acronym: bool,
+
+  /// Show the passphrase spelled with the NATO phonetic alphabet, e.g. to read it aloud.
+  #[arg(long)]
+  pub phonetic: bool,
+
+  /// Show the passphrase formatted with every preset. With `--json` or `--jsonl`, adds a `formats`
+  /// map of preset names to formatted passphrases instead.
+  #[arg(long)]
+  pub show_all_formats: bool,
+
+  /// Show the dice roll of each word.
+  #[arg(long)]
+  pub show_rolls: bool,
+
+  /// Number of decimal places to show entropy with.
+  #[arg(long, value_name = "N", default_value_t = 2)]
+  pub entropy_precision: usize,
+
+  /// Unit to show entropy in: `bits`, `nats`, or `words` for the number of words of the wordlist
+  /// in use with the same entropy.
+  #[arg(long, value_enum, value_name = "UNIT", default_value_t = EntropyUnit::Bits)]
+  pub entropy_unit: EntropyUnit,
+
+  /// Guesses per second to assume when estimating time to crack the passphrase.
+  #[arg(long, value_name = "RATE", default_value_t = 1e12)]
+  pub guess_rate: f64,
+
+  /// Don't print the link to more info about entropy.
+  #[arg(long)]
+  pub no_url: bool,
+
   /// Capitalize words.
   #[arg(short, long)]
   pub capitalize: bool,
 
-  /// Delimiter to use for joining words.
+  /// Lowercase the rest of capitalized words, e.g. `fOO` becomes `Foo`.
+  #[arg(long)]
+  pub normalize_case: bool,
+
+  /// Delimiter to join words with, keeping the rest of the preset. Falls back to
+  /// `DICEWARE_DELIMITER`.
   #[arg(short, long)]
   pub delimiter: Option<String>,
 
-  /// Formatting preset to use.
-  #[arg(short, long, value_parser = ["pascal", "kebab", "snake"])]
-  pub preset: Option<String>,
+  /// Group words by the given number, joining groups with the group delimiter.
+  #[arg(long, value_name = "SIZE")]
+  pub group_size: Option<usize>,
+
+  /// Delimiter to join groups of words with. Default is a space.
+  #[arg(long, value_name = "DELIMITER", requires = "group_size")]
+  pub group_delimiter: Option<String>,
+
+  /// Join words with delimiters drawn at random from the given chars, e.g. "-_.", adding a bit of
+  /// entropy per join.
+  #[arg(
+    long,
+    value_name = "CHARS",
+    allow_hyphen_values = true,
+    conflicts_with = "template"
+  )]
+  pub random_delim: Option<String>,
+
+  /// Fixed text to put before the passphrase. It adds no entropy.
+  #[arg(long)]
+  pub prefix: Option<String>,
+
+  /// Fixed text to put after the passphrase, e.g. `!2024`. It adds no entropy.
+  #[arg(long)]
+  pub suffix: Option<String>,
+
+  /// How much random digits to append.
+  #[arg(short, long, default_value_t = 0)]
+  pub numbers: usize,
+
+  /// Insert a random symbol, optionally picked from the given set.
+  #[arg(short, long, num_args = 0..=1, default_missing_value = Passphraser::SYMBOLS_DEFAULT)]
+  pub symbol: Option<String>,
+
+  /// Require character classes: `strict` requires an uppercase letter, a digit and a symbol.
+  #[arg(long, value_parser = ["strict"])]
+  pub policy: Option<String>,
+
+  /// Append a check word computed from the other words to catch typos.
+  #[arg(long)]
+  pub checksum: bool,
+
+  /// Shuffle words after picking them. This doesn't add any entropy.
+  #[arg(long)]
+  pub shuffle: bool,
+
+  /// Make sure that words of the passphrase are all distinct, re-rolling repeated ones. Each next
+  /// word then adds less entropy, so with `--min-entropy` words are added one at a time until the
+  /// target is met.
+  #[arg(long, conflicts_with = "rolls")]
+  pub unique_words: bool,
+
+  /// Formatting preset to use. Falls back to `DICEWARE_PRESET`.
+  #[arg(
+    short,
+    long,
+    value_parser = PossibleValuesParser::new(Preset::NAMES).try_map(|name| name.parse::<Preset>())
+  )]
+  pub preset: Option<Preset>,
+
+  /// Format using a template, e.g. `Wwww-dd-Wwww`: `W` is a capitalized word, `w` is a lowercased
+  /// word, `d` is a digit, `\` escapes the next char, and other chars are kept as is.
+  #[arg(
+    long,
+    conflicts_with_all = ["length", "min_entropy", "rolls", "delimiter", "preset", "random_case"]
+  )]
+  pub template: Option<String>,
+
+  /// Randomly capitalize words, adding a bit of entropy per word.
+  #[arg(short, long, conflicts_with_all = ["capitalize", "preset"])]
+  pub random_case: bool,
+
+  /// How many passphrases to generate, one per line.
+  #[arg(
+    long,
+    value_name = "N",
+    default_value_t = 1,
+    value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+    conflicts_with_all = ["rolls", "copy"]
+  )]
+  pub count: usize,
+
+  /// Print how long generation took and the rate of generated entropy, e.g. to compare wordlists
+  /// and configurations with `--count`.
+  #[arg(long, conflicts_with_all = ["json", "jsonl", "quiet"])]
+  pub stats: bool,
+
+  /// Make sure that passphrases generated with `--count` are all distinct, re-rolling duplicates.
+  #[arg(long, conflicts_with = "rolls")]
+  pub unique_candidates: bool,
+
+  /// Print passphrases with their entropy as a JSON array.
+  #[arg(long, conflicts_with = "output")]
+  pub json: bool,
+
+  /// Print passphrases with their entropy as JSON Lines, i.e. one JSON object per line, flushing
+  /// each line as soon as it's generated.
+  #[arg(long, conflicts_with_all = ["json", "output"])]
+  pub jsonl: bool,
+
+  /// Pick a passphrase interactively: space generates a new one, digits re-roll single words,
+  /// enter accepts it and escape cancels. Falls back to generating one passphrase if stdin is not
+  /// a terminal.
+  #[cfg(feature = "interactive")]
+  #[arg(
+    long,
+    conflicts_with_all = ["rolls", "count", "json", "jsonl", "output", "copy", "stats"]
+  )]
+  pub interactive: bool,
+
+  /// Print the passphrase as a QR code below it, e.g. to transfer it to a phone. Ignored with
+  /// `--quiet`.
+  #[cfg(feature = "qr")]
+  #[arg(long, conflicts_with_all = ["count", "json", "jsonl", "output"])]
+  pub qr: bool,
+
+  /// Print the QR code of the passphrase instead of the passphrase itself, even with `--quiet`.
+  #[cfg(feature = "qr")]
+  #[arg(long, conflicts_with_all = ["count", "json", "jsonl", "output"])]
+  pub qr_only: bool,
+
+  /// Source of randomness to generate passphrases with.
+  #[arg(long, value_enum, value_name = "SOURCE", default_value_t = RngSource::Thread)]
+  pub rng: RngSource,
+
+  /// Seed for `--rng seed`, making passphrases reproducible.
+  #[arg(long)]
+  pub seed: Option<u64>,
+
+  /// Print only the passphrase, without colors and entropy.
+  #[arg(short, long)]
+  pub quiet: bool,
+
+  /// Disable colored output. Also respects the `NO_COLOR` environment variable.
+  #[arg(long)]
+  pub no_color: bool,
+
+  /// Write the passphrase to the given file instead of printing it, or all of them, one per line,
+  /// with `--count`.
+  #[arg(short, long, value_name = "PATH")]
+  pub output: Option<String>,
+
+  /// Copy the passphrase to the clipboard.
+  #[arg(long)]
+  pub copy: bool,
+
+  /// Print the name, size and SHA-256 hash of the built-in wordlist and exit.
+  #[arg(long)]
+  pub version_wordlist: bool,
+
+  /// Check the wordlist for duplicate words and exit.
+  #[arg(long)]
+  pub check_duplicates: bool,
+
+  /// Print the number of words, word lengths, duplicates and entropy per word of the wordlist and
+  /// exit.
+  #[arg(long)]
+  pub show_wordlist_stats: bool,
+
+  /// Print completion script for the given shell and exit.
+  #[arg(long, value_name = "SHELL", hide = true)]
+  pub completions: Option<Shell>,
+}
+
+/// Sources of randomness that can be picked with `--rng`:
+///
+/// - `os`: OS random source, e.g. `getrandom` or `/dev/urandom`, queried for every number.
+/// - `thread`: thread-local CSPRNG, periodically reseeded from the OS random source.
+/// - `seed`: CSPRNG seeded with `--seed`. Not secure, since the seed has at most 64 bits.
+///
+/// Variants have no doc comments on purpose, since clap would switch help to the long layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RngSource {
+  Os,
+  Thread,
+  Seed,
+}
+
+/// Units entropy can be shown in with `--entropy-unit`:
+///
+/// - `bits`: binary units, the default.
+/// - `nats`: natural units, i.e. bits multiplied by `ln 2`.
+/// - `words`: number of words of the wordlist in use with the same entropy, e.g. each having about
+///   12.92 bits with the built-in wordlist.
+///
+/// Variants have no doc comments on purpose, since clap would switch help to the long layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EntropyUnit {
+  Bits,
+  Nats,
+  Words,
+}
+
+impl Cli {
+  /// Parses command-line arguments, falling back to `DICEWARE_*` environment variables for options
+  /// that aren't given. Fallbacks are skipped if conflicting options are given, e.g. the length is
+  /// not read from the environment if `--min-entropy` is given.
+  pub fn parse_with_env() -> Self {
+    Self::try_parse_with(env::args_os(), env_var).unwrap_or_else(|err| err.exit())
+  }
+
+  /// Same as [Cli::parse_with_env], but takes arguments and looks up variables with the given
+  /// function, returning an error instead of exiting.
+  fn try_parse_with<I, T>(args: I, var: impl Fn(&str) -> Option<String>) -> Result<Self, Error>
+  where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+  {
+    let matches = Self::command().try_get_matches_from(args)?;
+    let mut cli = Self::from_arg_matches(&matches)?;
+    let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+    if !given("length") && !given("min_entropy") && !given("rolls") {
+      if let Some(value) = var("DICEWARE_LENGTH") {
+        cli.length = parse_length(&value).map_err(|err| invalid_env("DICEWARE_LENGTH", err))?;
+      }
+    }
+
+    if !given("preset") && !cli.random_case {
+      if let Some(value) = var("DICEWARE_PRESET") {
+        cli.preset = Some(
+          value
+            .parse()
+            .map_err(|err| invalid_env("DICEWARE_PRESET", err))?,
+        );
+      }
+    }
+
+    if !given("delimiter") {
+      cli.delimiter = var("DICEWARE_DELIMITER").or(cli.delimiter);
+    }
+
+    Ok(cli)
+  }
+
+  /// Returns an error if the minimum entropy needs more words than the length can be set to with
+  /// the given builder, in line with [parse_length].
+  pub fn check_min_entropy(&self, builder: &Passphraser) -> Result<(), Error> {
+    match self.min_entropy {
+      | Some(bits) if builder.word_count() > LENGTH_MAX => {
+        Err(Self::command().error(
+          ErrorKind::ValueValidation,
+          format!("{bits} bits need more than {LENGTH_MAX} words, the maximum length"),
+        ))
+      },
+      | _ => Ok(()),
+    }
+  }
+}
+
+/// Returns the value of the given environment variable, treating empty values as unset.
+fn env_var(name: &str) -> Option<String> {
+  env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Creates a usage error for an invalid value of the given environment variable.
+fn invalid_env(name: &str, err: impl Display) -> Error {
+  Cli::command().error(
+    ErrorKind::InvalidValue,
+    format!("invalid value in {name}: {err}"),
+  )
+}
+
+/// Maximum number of words that can be requested with `--length`.
+const LENGTH_MAX: usize = 64;
+
+/// Parses the number of words, rejecting empty and unreasonably long passphrases.
+fn parse_length(value: &str) -> Result<usize, String> {
+  match value.parse::<usize>() {
+    | Ok(length) if (1..=LENGTH_MAX).contains(&length) => Ok(length),
+    | Ok(_) => Err(format!("passphrase must have from 1 to {LENGTH_MAX} words")),
+    | Err(err) => Err(err.to_string()),
+  }
+}
+
+/// Parses the minimum entropy in bits, rejecting targets that aren't positive and finite.
+fn parse_min_entropy(value: &str) -> Result<f32, String> {
+  match value.parse::<f32>() {
+    | Ok(bits) if bits.is_finite() && bits > 0.0 => Ok(bits),
+    | Ok(_) => Err("entropy must be a positive finite number of bits".to_string()),
+    | Err(err) => Err(err.to_string()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &[&str], vars: &[(&str, &str)]) -> Result<Cli, Error> {
+    let var = |name: &str| {
+      vars
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+    };
+
+    Cli::try_parse_with(["diceware"].iter().chain(args), var)
+  }
+
+  #[test]
+  fn test_env_precedence() {
+    let length = [("DICEWARE_LENGTH", "8")];
+
+    assert_eq!(parse(&[], &[]).unwrap().length, Passphraser::LENGTH_DEFAULT);
+    assert_eq!(parse(&[], &length).unwrap().length, 8);
+    assert_eq!(parse(&["-l", "4"], &length).unwrap().length, 4);
+
+    let cli = parse(&["-m", "30"], &length).unwrap();
+
+    assert_eq!(cli.length, Passphraser::LENGTH_DEFAULT);
+    assert_eq!(cli.min_entropy, Some(30.0));
+    assert!(parse(&[], &[("DICEWARE_LENGTH", "100")]).is_err());
+
+    let preset = [("DICEWARE_PRESET", "kebab")];
+
+    assert!(parse(&[], &[]).unwrap().preset.is_none());
+    assert!(matches!(
+      parse(&[], &preset).unwrap().preset,
+      Some(Preset::KebabCase)
+    ));
+    assert!(matches!(
+      parse(&["-p", "snake"], &preset).unwrap().preset,
+      Some(Preset::SnakeCase)
+    ));
+    assert!(parse(&["-r"], &preset).unwrap().preset.is_none());
+    assert!(parse(&[], &[("DICEWARE_PRESET", "nope")]).is_err());
+
+    let delimiter = [("DICEWARE_DELIMITER", "+")];
+
+    assert_eq!(parse(&[], &[]).unwrap().delimiter, None);
+    assert_eq!(
+      parse(&[], &delimiter).unwrap().delimiter.as_deref(),
+      Some("+")
+    );
+    assert_eq!(
+      parse(&["-d", "."], &delimiter)
+        .unwrap()
+        .delimiter
+        .as_deref(),
+      Some(".")
+    );
+  }
+
+  #[test]
+  fn test_min_entropy_bounds() {
+    for bits in ["inf", "NaN", "-1", "0"] {
+      assert!(parse(&["-m", bits], &[]).is_err());
+    }
+
+    let mut builder = Passphraser::new(1);
+    let cli = parse(&["-m", "1e12"], &[]).unwrap();
+    builder.min_entropy(1e12);
+
+    assert!(cli.check_min_entropy(&builder).is_err());
+
+    let cli = parse(&["-m", "800"], &[]).unwrap();
+    builder.min_entropy(800.0);
+
+    assert!(cli.check_min_entropy(&builder).is_ok());
+    let wordlist = ["foo".to_string(), "bar".to_string()];
+
+    assert!(cli.check_min_entropy(builder.wordlist(&wordlist)).is_err());
+  }
+
+  #[test]
+  fn test_count() {
+    assert_eq!(parse(&[], &[]).unwrap().count, 1);
+    assert_eq!(parse(&["--count", "3"], &[]).unwrap().count, 3);
+    assert!(parse(&["--count", "0"], &[]).is_err());
+  }
 }
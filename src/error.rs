@@ -0,0 +1,102 @@
+use std::fmt;
+
+use diceware::{GenerateError, RollsError, WordlistError};
+
+/// Errors that can occur when running the CLI, each having its own exit code.
+#[derive(Debug)]
+pub enum AppError {
+  /// Wordlist file doesn't exist or can't be read.
+  WordlistMissing,
+  /// Wordlist can't be read from stdin.
+  WordlistStdin,
+  /// Wordlist has no lines.
+  WordlistEmpty,
+  /// Wordlist is missing some indices or has duplicate ones.
+  WordlistInvalid(WordlistError),
+  /// Wordlist has the given number of duplicate words.
+  DuplicateWords(usize),
+  /// Passphrase can't be generated, e.g. because of a missing index.
+  Generate(GenerateError),
+  /// Passphrase has no words.
+  EmptyPassphrase,
+  /// Dice rolls are malformed.
+  InvalidRolls(RollsError),
+  /// Output file can't be written.
+  OutputUnwritable,
+  /// Interactive picking was cancelled.
+  #[cfg(feature = "interactive")]
+  Cancelled,
+  /// Banlist file doesn't exist or can't be read.
+  BanlistMissing,
+}
+
+impl AppError {
+  /// Returns the exit code for the error. Codes start from 3, since 1 is reserved for generic
+  /// failures, and 2 is used by clap for invalid arguments.
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      | Self::WordlistMissing => 3,
+      | Self::WordlistStdin => 4,
+      | Self::WordlistEmpty => 5,
+      | Self::WordlistInvalid(_) => 6,
+      | Self::DuplicateWords(_) => 7,
+      | Self::Generate(_) => 8,
+      | Self::EmptyPassphrase => 9,
+      | Self::InvalidRolls(_) => 10,
+      | Self::OutputUnwritable => 11,
+      #[cfg(feature = "interactive")]
+      | Self::Cancelled => 12,
+      | Self::BanlistMissing => 13,
+    }
+  }
+}
+
+impl fmt::Display for AppError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      | Self::WordlistMissing => {
+        write!(f, "Couldn't read the wordlist. Make sure the file exists.")
+      },
+      | Self::WordlistStdin => write!(f, "Couldn't read the wordlist from stdin."),
+      | Self::WordlistEmpty => {
+        write!(f, "Couldn't read the wordlist. Make sure it's not empty.")
+      },
+      | Self::WordlistInvalid(err) => write!(f, "Couldn't use the wordlist: {err}."),
+      | Self::DuplicateWords(count) => write!(f, "Found {count} duplicate words."),
+      | Self::Generate(err) => write!(f, "Couldn't generate a passphrase: {err}."),
+      | Self::EmptyPassphrase => {
+        write!(f, "Couldn't generate a passphrase with given parameters.")
+      },
+      | Self::InvalidRolls(err) => write!(f, "Couldn't use the dice rolls: {err}."),
+      | Self::OutputUnwritable => {
+        write!(
+          f,
+          "Couldn't write the passphrase. Make sure the path is writable."
+        )
+      },
+      #[cfg(feature = "interactive")]
+      | Self::Cancelled => write!(f, "Cancelled without picking a passphrase."),
+      | Self::BanlistMissing => {
+        write!(f, "Couldn't read the banlist. Make sure the file exists.")
+      },
+    }
+  }
+}
+
+impl From<WordlistError> for AppError {
+  fn from(err: WordlistError) -> Self {
+    Self::WordlistInvalid(err)
+  }
+}
+
+impl From<GenerateError> for AppError {
+  fn from(err: GenerateError) -> Self {
+    Self::Generate(err)
+  }
+}
+
+impl From<RollsError> for AppError {
+  fn from(err: RollsError) -> Self {
+    Self::InvalidRolls(err)
+  }
+}
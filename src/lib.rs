@@ -1,19 +1,67 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+#[cfg(feature = "thread-rng")]
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::Rng;
+#[cfg(feature = "thread-rng")]
+use rand::{RngCore, SeedableRng};
 
 static EFF_WORDLIST: &str = include_str!("../data/eff_long_wordlist.txt");
+static BIP39_ENGLISH_WORDLIST: &str = include_str!("../data/bip39_english_wordlist.txt");
+
+/// Number of words in the built-in EFF long wordlist, counted at compile time.
+pub const BUILTIN_WORDLIST_SIZE: usize = count_lines(EFF_WORDLIST);
+
+/// Lazily parsed built-in EFF long wordlist, so that it's split only once.
+static EFF_LINES: OnceLock<Arc<[String]>> = OnceLock::new();
+static EFF_ENTRIES: OnceLock<Arc<Entries>> = OnceLock::new();
 
 /// Represents a pair of an index, and a word associated with that index.
 pub(crate) type Pair = (usize, String);
 
+/// Represents a picked word along with its dice roll, if it was picked by rolling dice.
+pub(crate) type Pick = (String, Option<Vec<usize>>);
+
+/// Represents a parsed wordlist, mapping indices to words.
+pub(crate) type Entries = HashMap<usize, String>;
+
 /// Formatting presets.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Preset {
   /// Format using `PascalCase` style.
   PascalCase,
+  /// Format using `camelCase` style.
+  CamelCase,
   /// Format using `kebab-case` style.
   KebabCase,
   /// Format using `snake_case` style.
   SnakeCase,
+  /// Format using `Title Case` style.
+  TitleCase,
+  /// Format using `UPPER CASE` style.
+  UpperCase,
+  /// Format using `lower case` style.
+  LowerCase,
+  /// Format using randomly capitalized words joined by spaces. Each word is capitalized with 50%
+  /// probability during generation, adding one bit of entropy per word.
+  RandomCase,
+  /// Format using words joined by spaces, capitalizing only words at provided positions, starting
+  /// from 0. Positions out of range are ignored.
+  CapitalizePositions(Vec<usize>),
+  /// Format using provided delimiters, cycling through them between words. If no delimiters are
+  /// provided, the default one is used.
+  Delimiters(Vec<String>),
+  /// Format using a [Template], e.g. `Wwww-dd-Wwww`.
+  Template(Template),
   /// Format using provided parameters.
   Arbitrary {
     /// Whether to capitalize a word or not.
@@ -27,13 +75,306 @@ pub enum Preset {
 }
 
 impl Preset {
-  /// Creates a [Preset] from given string (excepting [Preset::Arbitrary]).
+  /// Names of presets that can be created with [Preset::try_from_name].
+  pub const NAMES: [&'static str; 7] = [
+    "pascal", "camel", "kebab", "snake", "title", "upper", "lower",
+  ];
+
+  /// Creates a [Preset] from given string (excepting [Preset::Arbitrary]). Unknown names silently
+  /// fall back to [Preset::Default].
+  #[deprecated(
+    since = "1.1.0",
+    note = "use `Preset::try_from_name` or `str::parse` instead, which reject unknown names"
+  )]
   pub fn from(preset_name: &str) -> Self {
-    match preset_name {
-      | "pascal" => Self::PascalCase,
-      | "kebab" => Self::KebabCase,
-      | "snake" => Self::SnakeCase,
-      | _ => Self::Default,
+    Self::try_from_name(preset_name).unwrap_or_default()
+  }
+
+  /// Creates a [Preset] from one of [Preset::NAMES], or returns [None] if the name is unknown.
+  pub fn try_from_name(name: &str) -> Option<Self> {
+    match name {
+      | "pascal" => Some(Self::PascalCase),
+      | "camel" => Some(Self::CamelCase),
+      | "kebab" => Some(Self::KebabCase),
+      | "snake" => Some(Self::SnakeCase),
+      | "title" => Some(Self::TitleCase),
+      | "upper" => Some(Self::UpperCase),
+      | "lower" => Some(Self::LowerCase),
+      | _ => None,
+    }
+  }
+
+  /// Returns [FormatOptions] the preset formats with, or [None] if it can't be expressed with them,
+  /// e.g. [Preset::RandomCase] or [Preset::Template].
+  pub fn format_options(&self) -> Option<FormatOptions> {
+    let options = |delimiter: &str, capitalize: bool, case: Case| {
+      Some(FormatOptions {
+        delimiter: delimiter.to_string(),
+        capitalize,
+        case,
+        ..FormatOptions::default()
+      })
+    };
+
+    match self {
+      | Self::PascalCase => options(Passphrase::DELIM_PASCALCASE, true, Case::Keep),
+      | Self::CamelCase => options(Passphrase::DELIM_PASCALCASE, false, Case::Camel),
+      | Self::KebabCase => options(Passphrase::DELIM_KEBABCASE, false, Case::Keep),
+      | Self::SnakeCase => options(Passphrase::DELIM_SNAKECASE, false, Case::Keep),
+      | Self::TitleCase => options(Passphrase::DELIM_TITLECASE, true, Case::Keep),
+      | Self::UpperCase => options(Passphrase::DELIM_DEFAULT, false, Case::Upper),
+      | Self::LowerCase => options(Passphrase::DELIM_DEFAULT, false, Case::Lower),
+      | Self::Delimiters(delimiters) if delimiters.is_empty() => {
+        options(Passphrase::DELIM_DEFAULT, false, Case::Keep)
+      },
+      | Self::Arbitrary {
+        capitalize,
+        delimiter,
+      } => {
+        options(
+          delimiter.as_deref().unwrap_or(Passphrase::DELIM_DEFAULT),
+          *capitalize,
+          Case::Keep,
+        )
+      },
+      | Self::Default => options(Passphrase::DELIM_DEFAULT, false, Case::Keep),
+      | Self::RandomCase
+      | Self::CapitalizePositions(_)
+      | Self::Delimiters(_)
+      | Self::Template(_) => None,
+    }
+  }
+}
+
+impl FromStr for Preset {
+  type Err = ParsePresetError;
+
+  fn from_str(name: &str) -> Result<Self, Self::Err> {
+    Self::try_from_name(name).ok_or_else(|| {
+      ParsePresetError {
+        name: name.to_string(),
+      }
+    })
+  }
+}
+
+/// Parameters of formatting words, as a lower-level alternative to presets. See
+/// [Passphrase::format_opts].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+  /// Delimiter to join words with.
+  pub delimiter: String,
+  /// Whether to capitalize words, after changing their case.
+  pub capitalize: bool,
+  /// Case to change words to.
+  pub case: Case,
+  /// Fixed text to put before the formatted passphrase. It adds no entropy.
+  pub prefix: String,
+  /// Fixed text to put after the formatted passphrase. It adds no entropy.
+  pub suffix: String,
+}
+
+/// Cases that [FormatOptions] can change words to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Case {
+  /// Keep words as is.
+  #[default]
+  Keep,
+  /// Lowercase words.
+  Lower,
+  /// Uppercase words.
+  Upper,
+  /// Lowercase the first word and capitalize the rest, i.e. `camelCase` when joined without a
+  /// delimiter.
+  Camel,
+}
+
+/// Pattern that passphrases are formatted with, as a flexible alternative to other presets. The
+/// grammar is as follows:
+///
+/// - `W` is a capitalized word.
+/// - `w` is a lowercased word.
+/// - `d` is a random digit.
+/// - `\` escapes the next char, e.g. `\d` is a literal `d`. A trailing `\` is a literal `\`.
+/// - Any other char is passed through as is.
+///
+/// For example, `Wwww-dd-Wwww` gives something like `Foobarbaz-42-Quxquuxcorge`. Words and digits
+/// generated on top of the template, e.g. the check word or [Passphraser::numbers], are appended to
+/// the end, with words separated by spaces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Template {
+  tokens: Vec<TemplateToken>,
+}
+
+/// Single token of a [Template].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum TemplateToken {
+  /// Word, either capitalized or lowercased.
+  Word { capitalized: bool },
+  /// Random digit.
+  Digit,
+  /// Char passed through as is.
+  Literal(char),
+}
+
+impl Template {
+  /// Parses a template from the given pattern. See [Template] for the grammar.
+  pub fn new(pattern: &str) -> Self {
+    let mut chars = pattern.chars();
+    let mut tokens = Vec::new();
+
+    while let Some(c) = chars.next() {
+      let token = match c {
+        | 'W' => TemplateToken::Word { capitalized: true },
+        | 'w' => TemplateToken::Word { capitalized: false },
+        | 'd' => TemplateToken::Digit,
+        | '\\' => TemplateToken::Literal(chars.next().unwrap_or('\\')),
+        | c => TemplateToken::Literal(c),
+      };
+
+      tokens.push(token);
+    }
+
+    Self { tokens }
+  }
+
+  /// Returns the number of words in the template.
+  pub fn word_count(&self) -> usize {
+    self
+      .tokens
+      .iter()
+      .filter(|token| matches!(token, TemplateToken::Word { .. }))
+      .count()
+  }
+
+  /// Returns the number of digits in the template.
+  pub fn digit_count(&self) -> usize {
+    self
+      .tokens
+      .iter()
+      .filter(|token| matches!(token, TemplateToken::Digit))
+      .count()
+  }
+
+  /// Renders the template, taking words and digits in order and capitalizing words with the given
+  /// function. Tokens left without a word or a digit are skipped.
+  fn render(&self, words: &[String], digits: &str, capitalize: impl Fn(&str) -> String) -> String {
+    let mut words = words.iter();
+    let mut digits = digits.chars();
+    let mut rendered = String::new();
+
+    for token in &self.tokens {
+      match token {
+        | TemplateToken::Word { capitalized: true } => {
+          rendered.extend(words.next().map(|word| capitalize(word)));
+        },
+        | TemplateToken::Word { capitalized: false } => {
+          rendered.extend(words.next().map(|word| word.to_lowercase()));
+        },
+        | TemplateToken::Digit => rendered.extend(digits.next()),
+        | TemplateToken::Literal(c) => rendered.push(*c),
+      }
+    }
+
+    for word in words {
+      rendered.push(' ');
+      rendered.push_str(word);
+    }
+
+    rendered.extend(digits);
+    rendered
+  }
+}
+
+/// Wordlists bundled with the crate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BuiltinWordlist {
+  /// EFF long wordlist with 7776 words, picked by rolling five dice.
+  #[default]
+  EffLong,
+  /// BIP39 English wordlist with 2048 words, picked uniformly, giving 11 bits of entropy per word.
+  /// Note that this produces uniformly random word sequences, not valid BIP39 mnemonics, since the
+  /// checksum is not calculated.
+  Bip39English,
+}
+
+impl BuiltinWordlist {
+  /// Returns lines of the wordlist.
+  pub fn lines(&self) -> Vec<String> {
+    match self {
+      | Self::EffLong => builtin_wordlist(),
+      | Self::Bip39English => parse_wordlist(BIP39_ENGLISH_WORDLIST),
+    }
+  }
+
+  /// Returns the number of dice rolled per word, or [None] if the wordlist is plain, i.e. has no
+  /// indices.
+  pub fn dice_count(&self) -> Option<usize> {
+    match self {
+      | Self::EffLong => Some(5),
+      | Self::Bip39English => None,
+    }
+  }
+}
+
+/// Schemes of building wordlist indices from dice rolls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndexScheme {
+  /// Rolled numbers are concatenated as decimal digits, e.g. `11111` to `66666` for five six-sided
+  /// dice, as in the EFF wordlists. Only works for dice with up to 9 faces.
+  #[default]
+  Concatenated,
+  /// Rolls are digits of a number in the base of the number of faces, giving contiguous 0-based
+  /// indices, e.g. `0` to `7775` for five six-sided dice, or `0000` to `9999` for four ten-sided
+  /// dice. Indices of merged wordlists must be zero-padded, see [Passphraser::wordlists].
+  Positional,
+}
+
+impl IndexScheme {
+  /// Builds an index from given roll of dice with the given number of faces, numbered from 1.
+  /// Numbers out of range of the dice, e.g. 0, give meaningless indices rather than panicking, so
+  /// rolls from untrusted input should be checked first, as
+  /// [Passphraser::generate_from_rolls_with] does.
+  pub fn index(&self, roll: &[usize], dice_faces: usize) -> usize {
+    match self {
+      | Self::Concatenated => {
+        roll
+          .iter()
+          .fold(0, |acc: usize, n| acc.wrapping_mul(10).wrapping_add(*n))
+      },
+      | Self::Positional => {
+        roll.iter().fold(0, |acc: usize, n| {
+          acc.wrapping_mul(dice_faces).wrapping_add(n.wrapping_sub(1))
+        })
+      },
+    }
+  }
+
+  /// Returns all indices that can be rolled with given dice, in ascending order.
+  pub fn indices(&self, dice_count: usize, dice_faces: usize) -> Vec<usize> {
+    (0..dice_faces.pow(dice_count as u32))
+      .map(|n| {
+        let roll = (0..dice_count)
+          .rev()
+          .map(|position| n / dice_faces.pow(position as u32) % dice_faces + 1)
+          .collect::<Vec<_>>();
+
+        self.index(&roll, dice_faces)
+      })
+      .collect()
+  }
+
+  /// Returns the number of decimal digits indices take, which is also the position of the die
+  /// selecting a merged wordlist.
+  fn digits(&self, dice_count: usize, dice_faces: usize) -> u32 {
+    match self {
+      | Self::Concatenated => dice_count as u32,
+      | Self::Positional => {
+        let max = dice_faces.pow(dice_count as u32).saturating_sub(1);
+        max.checked_ilog10().unwrap_or(0) + 1
+      },
     }
   }
 }
@@ -62,23 +403,139 @@ impl Preset {
 ///
 /// let passphrase = builder.generate();
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Passphraser {
   /// Number of words to generate.
   length: usize,
-  /// Wordlist to pick words from.
-  wordlist: Vec<String>,
+  /// Wordlist to pick words from, shared to avoid copying it.
+  wordlist: Arc<[String]>,
+  /// Wordlist parsed into index-word entries for fast lookups.
+  entries: Arc<Entries>,
+  /// Whether to lowercase words of wordlists when they're set. Default is `false`.
+  normalize_wordlist: bool,
   /// Formatting preset to use. Default is [Preset::Default].
   preset: Preset,
+  /// Delimiter replacing the one of the preset. If not set, the preset's delimiter is used.
+  delimiter: Option<String>,
+  /// Number of words per group, or 0 if words are not grouped. Default is 0.
+  group_size: usize,
+  /// Delimiter to join groups of words with. If not set, a space is used.
+  group_delimiter: Option<String>,
+  /// Delimiters to draw from for each join of words. Default is empty, i.e. none are drawn.
+  random_delimiters: Vec<String>,
+  /// Fixed text to put before the formatted passphrase. Default is empty.
+  prefix: String,
+  /// Fixed text to put after the formatted passphrase. Default is empty.
+  suffix: String,
+  /// Number of dice rolled per word. Default is 5.
+  dice_count: usize,
+  /// Number of faces each die has. Default is 6.
+  dice_faces: usize,
+  /// Scheme of building indices from rolls. Default is [IndexScheme::Concatenated].
+  index_scheme: IndexScheme,
+  /// Number of merged wordlists, one of which is picked by an extra die per word. Default is 1.
+  lists: usize,
+  /// Seed for reproducible generation. If not set, the thread-local RNG is used.
+  seed: Option<u64>,
+  /// Number of random digits to append to the passphrase. Default is 0.
+  numbers: usize,
+  /// Set of symbols to pick a random symbol from. If not set, no symbol is inserted.
+  symbols: Option<Vec<char>>,
+  /// Minimum entropy of words in bits. If set, it takes precedence over the number of words.
+  min_entropy: Option<f32>,
+  /// Minimum number of chars of the formatted passphrase. Default is 0.
+  min_chars: usize,
+  /// Substrings that words must not contain.
+  exclude: Vec<String>,
+  /// Lowercased words that are re-rolled if picked, shared to avoid copying them.
+  banlist: Arc<HashSet<String>>,
+  /// Whether to capitalize words on top of the preset. Default is `false`.
+  capitalize: bool,
+  /// Whether to lowercase the rest of capitalized words. Default is `false`.
+  normalize_case: bool,
+  /// Whether to append a check word computed from the other words. Default is `false`.
+  checksum: bool,
+  /// Whether to shuffle picked words. Default is `false`.
+  shuffle: bool,
+  /// Whether to re-roll words that were already picked. Default is `false`.
+  unique_words: bool,
+  /// Whether to bias picks toward shorter words. Default is `false`.
+  prefer_short: bool,
+  /// Character classes that passphrases must contain. Default is none.
+  policy: Policy,
+  /// Counts derived from the wordlist and the settings filtering it, counted on first use.
+  counts: OnceLock<Counts>,
+}
+
+/// Counts derived from the wordlist, kept by [Passphraser] since counting walks the whole wordlist.
+/// They're dropped whenever the wordlist or any setting filtering it changes.
+#[derive(Clone, Copy, Debug)]
+struct Counts {
+  /// Number of unique words that can be picked.
+  possibilities: usize,
+  /// Number of unique words that can be looked up by rolled indices.
+  roll_possibilities: usize,
+  /// Entropy per word if words aren't picked uniformly, i.e. of merged or short-biased picks.
+  bits_per_word: Option<f32>,
 }
 
 impl Passphraser {
+  const DICE_COUNT_DEFAULT: usize = 5;
+  const DICE_FACES_DEFAULT: usize = 6;
+  /// Maximum number of duplicates in a row that [Passphraser::generate_many_with] re-rolls before
+  /// giving up.
+  pub const DUPLICATE_RETRIES_MAX: usize = 100;
+  /// Info string used for key derivation in [Passphraser::from_key].
+  #[cfg(feature = "kdf")]
+  pub const KDF_INFO: &'static [u8] = b"diceware";
+  /// Number of words to generate after [Passphraser::reset].
+  pub const LENGTH_DEFAULT: usize = 6;
+  /// Maximum number of words to generate when adding words to reach the minimum number of chars.
+  pub const MIN_CHARS_WORDS_MAX: usize = 100;
+  /// Maximum number of words to generate when calculating it from the minimum entropy, so that
+  /// unreachable targets, e.g. infinite ones, don't exhaust memory.
+  pub const MIN_ENTROPY_WORDS_MAX: usize = 1_000;
+  /// Maximum number of words in a row that are re-rolled before giving up, e.g. because they were
+  /// already picked with [Passphraser::unique_words] or are banned with [Passphraser::banlist].
+  pub const REROLLS_MAX: usize = 10_000;
+  /// Default set of symbols to pick from when inserting a symbol.
+  pub const SYMBOLS_DEFAULT: &'static str = "!@#$%^&*";
+  /// Maximum number of wordlists that can be merged with [Passphraser::wordlists].
+  pub const WORDLISTS_MAX: usize = 9;
+
   /// Create builder with specified number of words to generate.
   pub fn new(length: usize) -> Self {
     Self {
       length,
-      wordlist: builtin_wordlist(),
+      wordlist: builtin_lines().clone(),
+      entries: builtin_entries().clone(),
+      normalize_wordlist: false,
       preset: Preset::Default,
+      delimiter: None,
+      group_size: 0,
+      group_delimiter: None,
+      random_delimiters: Vec::new(),
+      prefix: String::new(),
+      suffix: String::new(),
+      dice_count: Self::DICE_COUNT_DEFAULT,
+      dice_faces: Self::DICE_FACES_DEFAULT,
+      index_scheme: IndexScheme::Concatenated,
+      lists: 1,
+      seed: None,
+      numbers: 0,
+      symbols: None,
+      min_entropy: None,
+      min_chars: 0,
+      exclude: Vec::new(),
+      banlist: Arc::default(),
+      capitalize: false,
+      normalize_case: false,
+      checksum: false,
+      shuffle: false,
+      unique_words: false,
+      prefer_short: false,
+      policy: Policy::default(),
+      counts: OnceLock::new(),
     }
   }
 
@@ -88,204 +545,4967 @@ impl Passphraser {
     self
   }
 
-  /// Set the wordlist to pick words from.
+  /// Set the wordlist to pick words from. Wordlists can either have `<index> <word>` pairs, with
+  /// words picked by rolling dice, or be plain, with one word per line. Words from plain wordlists
+  /// are picked uniformly, and entropy is calculated using the number of words.
   pub fn wordlist<'a>(&'a mut self, list: &'a [String]) -> &'a mut Self {
-    self.wordlist = list.to_vec();
-    self
+    self.wordlist_shared(list.into())
   }
 
-  /// Set the formatting preset.
-  pub fn preset(&mut self, preset: Preset) -> &mut Self {
-    self.preset = preset;
+  /// Same as [Passphraser::wordlist], but takes ownership of the wordlist instead of copying it.
+  pub fn wordlist_owned(&mut self, list: Vec<String>) -> &mut Self {
+    self.wordlist_shared(list.into())
+  }
+
+  /// Same as [Passphraser::wordlist], but shares the wordlist instead of copying it, e.g. between
+  /// several builders.
+  pub fn wordlist_shared(&mut self, list: Arc<[String]>) -> &mut Self {
+    let list =
+      if self.normalize_wordlist && list.iter().any(|line| line.chars().any(char::is_uppercase)) {
+        list.iter().map(|line| line.to_lowercase()).collect()
+      } else {
+        list
+      };
+
+    self.entries = Arc::new(to_entries(&list));
+    self.wordlist = list;
+    self.lists = 1;
+    self.counts.take();
     self
   }
 
-  /// Roll dice, generate passphrase words, calculate entropy and return a [Passphrase].
-  pub fn generate(&self) -> Passphrase {
-    let rolls = roll_dice(self.length, 5, 1, 6);
-    let words = passphrase(&self.wordlist, rolls);
+  /// Merge several indexed wordlists, all using the configured dice, into one. Each word is then
+  /// picked with an extra die that selects one of the lists uniformly, followed by the usual dice
+  /// within the selected list, so rolls have one more number. Entropy per word is
+  ///
+  /// `log2(lists) + (log2(words_1) + ... + log2(words_n)) / lists`
+  ///
+  /// where `words_i` is the number of unique words that can be rolled in the `i`-th list. Words
+  /// shared between lists are counted in each of them. If words are excluded, words are picked
+  /// uniformly from all lists instead. List numbers prefix indices as is, so indices must take the
+  /// same number of digits, e.g. be zero-padded with [IndexScheme::Positional].
+  ///
+  /// # Panics
+  ///
+  /// Panics if no lists or more than [Passphraser::WORDLISTS_MAX] lists are given.
+  pub fn wordlists(&mut self, lists: &[&[String]]) -> &mut Self {
+    assert!(
+      (1..=Self::WORDLISTS_MAX).contains(&lists.len()),
+      "expected 1 to {} wordlists, got {}",
+      Self::WORDLISTS_MAX,
+      lists.len()
+    );
 
-    let entropy = Entropy::new(self.wordlist.len(), self.length);
+    let merged = lists
+      .iter()
+      .enumerate()
+      .flat_map(|(list, lines)| {
+        lines.iter().filter_map(move |line| {
+          to_pair(to_components(line)).map(|(index, word)| format!("{}{index}\t{word}", list + 1))
+        })
+      })
+      .collect::<Vec<_>>();
 
-    Passphrase {
-      words,
-      preset: self.preset.clone(),
-      entropy,
-    }
+    self.wordlist_owned(merged);
+    self.lists = lists.len();
+    self
   }
-}
 
-/// Contains information about entropy.
-#[derive(Debug)]
-pub struct Entropy {
-  /// How much unique words (possibilites) contains the wordlist.
-  pub possibilities: usize,
-  /// Calculated entropy of the passphrase.
-  pub entropy: f32,
-}
+  /// Set whether to lowercase words of wordlists, e.g. of custom ones with mixed case, so that
+  /// capitalizing presets behave consistently. Words are lowercased once, when the wordlist is set,
+  /// or right away if it's already set. Turning it off doesn't restore the original case.
+  pub fn normalize_wordlist(&mut self, normalize: bool) -> &mut Self {
+    self.normalize_wordlist = normalize;
 
-impl Entropy {
-  pub fn new(possibilities: usize, phrase_length: usize) -> Self {
-    Entropy {
-      possibilities,
-      entropy: calc_entropy(possibilities, phrase_length),
+    if normalize {
+      let lists = self.lists;
+      self.wordlist_shared(self.wordlist.clone());
+      self.lists = lists;
     }
+
+    self
   }
-}
 
-/// Contains generated passphrase words, formatting preset and calculated entropy.
-#[derive(Debug)]
-pub struct Passphrase {
-  preset: Preset,
-  entropy: Entropy,
-  words: Vec<String>,
-}
+  /// Set the wordlist to one of the built-in ones, adjusting dice configuration accordingly.
+  pub fn builtin(&mut self, builtin: BuiltinWordlist) -> &mut Self {
+    if builtin == BuiltinWordlist::EffLong {
+      self.wordlist = builtin_lines().clone();
+      self.entries = builtin_entries().clone();
+      self.lists = 1;
+    } else {
+      self.wordlist_owned(builtin.lines());
+    }
 
-impl Passphrase {
-  const DELIM_DEFAULT: &'static str = " ";
-  const DELIM_KEBABCASE: &'static str = "-";
-  const DELIM_PASCALCASE: &'static str = "";
-  const DELIM_SNAKECASE: &'static str = "_";
+    self.dice_count = builtin.dice_count().unwrap_or(Self::DICE_COUNT_DEFAULT);
+    self.dice_faces = Self::DICE_FACES_DEFAULT;
+    self.index_scheme = IndexScheme::Concatenated;
+    self.counts.take();
+    self
+  }
 
-  /// Returns generated passphrase words.
-  pub fn words(&self) -> &Vec<String> {
-    &self.words
+  /// Resets the whole configuration to defaults, as if the builder was just created with
+  /// [Passphraser::LENGTH_DEFAULT] words, e.g. to reuse a long-lived builder.
+  pub fn reset(&mut self) -> &mut Self {
+    *self = Self::new(Self::LENGTH_DEFAULT);
+    self
   }
 
-  /// Returns calculated passphrase [Entropy].
-  pub fn entropy(&self) -> &Entropy {
-    &self.entropy
+  /// Resets the preset to [Preset::Default], dropping the delimiter override, random delimiters,
+  /// grouping, prefix and suffix.
+  pub fn reset_preset(&mut self) -> &mut Self {
+    self.preset = Preset::Default;
+    self.delimiter = None;
+    self.group_size = 0;
+    self.group_delimiter = None;
+    self.random_delimiters.clear();
+    self.prefix.clear();
+    self.suffix.clear();
+    self
   }
 
-  /// Formats passphrase using the passphrase's preset.
-  pub fn format(&self) -> String {
-    self.format_with(&self.preset)
+  /// Resets the wordlist to the built-in EFF long wordlist, along with the dice configuration.
+  pub fn reset_wordlist(&mut self) -> &mut Self {
+    self.builtin(BuiltinWordlist::EffLong)
   }
 
-  /// Formats passphrase using the given preset.
-  pub fn format_with(&self, preset: &Preset) -> String {
-    match &preset {
-      | Preset::PascalCase => self.format_using(Self::DELIM_PASCALCASE, true),
-      | Preset::KebabCase => self.format_using(Self::DELIM_KEBABCASE, false),
-      | Preset::SnakeCase => self.format_using(Self::DELIM_SNAKECASE, false),
-      | Preset::Arbitrary {
-        capitalize,
-        delimiter,
-      } => {
-        let default = Self::DELIM_DEFAULT.to_string();
-        let delimiter = delimiter.clone().unwrap_or(default);
+  /// Set the formatting template, overriding the preset. The number of words and digits is then
+  /// taken from the template, so the length and minimum entropy are ignored, while digits set with
+  /// [Passphraser::numbers] are appended. See [Template] for the grammar.
+  pub fn template(&mut self, template: &str) -> &mut Self {
+    self.preset = Preset::Template(Template::new(template));
+    self
+  }
 
-        self.format_using(&delimiter, *capitalize)
-      },
-      | Preset::Default => self.format_using(Self::DELIM_DEFAULT, false),
-    }
+  /// Set the delimiter to join words with, replacing the one of the preset while keeping the rest
+  /// of its styling, e.g. [Preset::PascalCase] with `-` gives `Foo-Bar-Baz`. Doesn't apply to
+  /// [Preset::Template].
+  pub fn delimiter(&mut self, delimiter: &str) -> &mut Self {
+    self.delimiter = Some(delimiter.to_string());
+    self
   }
 
-  /// Joins words using specified delimiter and optionally capitalizes them.
-  fn format_using(&self, delimiter: &str, capitalize: bool) -> String {
-    let words = if capitalize {
-      self
-        .words
-        .iter()
-        .map(|word| to_capitalized(word))
-        .collect::<Vec<_>>()
-    } else {
-      self.words.clone()
-    };
+  /// Same as [Passphraser::delimiter], named to pair with [Passphraser::group_delimiter].
+  pub fn word_delimiter(&mut self, delimiter: &str) -> &mut Self {
+    self.delimiter(delimiter)
+  }
 
-    words.join(delimiter)
+  /// Set the number of words per group, e.g. 2 for `abc-def ghi-jkl`. Words within groups are
+  /// joined with the preset's delimiter or [Passphraser::word_delimiter], and groups are joined
+  /// with [Passphraser::group_delimiter]. Doesn't apply to [Preset::Delimiters] and
+  /// [Preset::Template]. Default is 0, i.e. no grouping.
+  pub fn group_size(&mut self, group_size: usize) -> &mut Self {
+    self.group_size = group_size;
+    self
   }
-}
 
-/// Rolls a dice, producing a vector of numbers for each run.
-pub fn roll_dice(runs: usize, rolls: usize, start: usize, end: usize) -> Vec<Vec<usize>> {
-  let mut rng = rand::thread_rng();
+  /// Set the delimiter to join groups of words with. Default is a space.
+  pub fn group_delimiter(&mut self, delimiter: &str) -> &mut Self {
+    self.group_delimiter = Some(delimiter.to_string());
+    self
+  }
 
-  (1..=runs)
-    .map(|_| (1..=rolls).map(|_| rng.gen_range(start..end)).collect())
-    .collect()
-}
+  /// Set delimiters to draw from at random for each join of words, replacing the delimiter of the
+  /// preset, so that passphrases resist simple splitting. Each drawn delimiter adds
+  /// `log2(delimiters)` bits of entropy. Doesn't apply to [Preset::Delimiters] with delimiters and
+  /// [Preset::Template], and groups are still joined with the group delimiter. Repeated delimiters
+  /// are ignored.
+  pub fn random_delimiters(&mut self, delimiters: &[String]) -> &mut Self {
+    self.random_delimiters = delimiters.iter().fold(Vec::new(), |mut acc, delimiter| {
+      if !acc.contains(delimiter) {
+        acc.push(delimiter.clone());
+      }
 
-/// Given a wordlist and dice rolls, generates a Diceware passphrase as a [Vec] of words.
-pub fn passphrase(lines: &[String], dice_rolls: Vec<Vec<usize>>) -> Vec<String> {
-  let words = dice_rolls.iter().fold(Vec::new(), |acc, roll| {
-    let rolled_index = to_index(roll.to_vec());
+      acc
+    });
 
-    let rolled_word = lines.iter().find_map(|line| {
-      let components = to_components(line);
-      let pair = to_pair(components);
+    self
+  }
 
-      match pair {
-        | Some((index, word)) if rolled_index == index => Some(word),
-        | _ => None,
-      }
-    });
+  /// Set fixed text to put before the formatted passphrase, e.g. a token required by a password
+  /// policy. It's known to anyone, so it adds no entropy and isn't accounted for.
+  pub fn prefix(&mut self, prefix: &str) -> &mut Self {
+    self.prefix = prefix.to_string();
+    self
+  }
 
-    if let Some(word) = rolled_word {
-      [acc, vec![word]].concat()
-    } else {
-      acc
-    }
-  });
+  /// Set fixed text to put after the formatted passphrase, e.g. `!2024`. Like the prefix, it adds
+  /// no entropy and isn't accounted for.
+  pub fn suffix(&mut self, suffix: &str) -> &mut Self {
+    self.suffix = suffix.to_string();
+    self
+  }
 
-  words
-}
+  /// Set the formatting preset.
+  pub fn preset(&mut self, preset: Preset) -> &mut Self {
+    self.preset = preset;
+    self
+  }
 
-/// Reads a built-in EFF long wordlist and returns a vector of lines.
-pub fn builtin_wordlist() -> Vec<String> {
-  EFF_WORDLIST.lines().map(str::to_string).collect()
-}
+  /// Set whether to capitalize words on top of the formatting preset, e.g. to get `Correct-Horse`
+  /// with [Preset::KebabCase].
+  pub fn capitalize(&mut self, capitalize: bool) -> &mut Self {
+    self.capitalize = capitalize;
+    self
+  }
 
-/// Given a length (the number of possibilities, e.g. for the EFF long list it is 7776
-/// possibilities) of a wordlist and phrase length in words, calculates entropy of the phrase.
-pub fn calc_entropy(possibilities: usize, phrase_length: usize) -> f32 {
-  (possibilities as f32).log2() * (phrase_length as f32)
-}
+  /// Set whether to lowercase the rest of every capitalized word, e.g. to get `Foo` instead of
+  /// `FOo` from `fOo`. By default only the first char is changed.
+  pub fn normalize_case(&mut self, normalize_case: bool) -> &mut Self {
+    self.normalize_case = normalize_case;
+    self
+  }
 
-/// Splits a given line into a vector of components.
-pub(crate) fn to_components(line: &str) -> Vec<&str> {
-  line.split_ascii_whitespace().collect()
-}
+  /// Set whether to append a check word to the passphrase, which helps to catch typos when the
+  /// passphrase is transcribed. See [Passphraser::check_word] for how it's computed. The check word
+  /// is derived from the other words, so it doesn't add any entropy.
+  pub fn checksum(&mut self, checksum: bool) -> &mut Self {
+    self.checksum = checksum;
+    self
+  }
 
-/// Unpacks a given vector of line components in the form of `[index, word]` to a [Pair] struct.
-pub(crate) fn to_pair(components: Vec<&str>) -> Option<Pair> {
-  let mut components = components.iter();
+  /// Set whether to shuffle picked words using the same RNG. See [Passphrase::shuffle].
+  pub fn shuffle(&mut self, shuffle: bool) -> &mut Self {
+    self.shuffle = shuffle;
+    self
+  }
 
-  if let (Some(index), Some(word)) = (components.next(), components.next()) {
-    match index.parse::<usize>() {
-      | Ok(index) => Some((index, word.to_string())),
-      | Err(_) => None,
-    }
-  } else {
-    None
+  /// Set whether all words must be distinct, re-rolling words that were already picked. Entropy
+  /// is then calculated for sampling without replacement, i.e. `log2(N) + log2(N - 1) + ...`.
+  /// Checked generation fails if there are fewer words to pick from than requested, while
+  /// unchecked generation picks as many words as there are. Doesn't apply to given rolls.
+  pub fn unique_words(&mut self, unique_words: bool) -> &mut Self {
+    self.unique_words = unique_words;
+    self
   }
-}
 
-/// Reduces a vector of rolled numbers to a single number which then will be used as an index in a
-/// Diceware wordlist.
-pub(crate) fn to_index(ns: Vec<usize>) -> usize {
-  ns.iter().fold(0, |acc, n| acc * 10 + n)
-}
+  /// Set whether to bias picks toward shorter words, e.g. for easier typing. Each distinct word is
+  /// weighted inversely proportional to its length in chars, i.e. with `1 / length`, so a word of 4
+  /// chars is twice as likely as a word of 8 chars. Words are then picked without rolls, as with
+  /// excluded words. Entropy per word is the Shannon entropy of the resulting distribution,
+  ///
+  /// `-(p_1 * log2(p_1) + ... + p_n * log2(p_n))`
+  ///
+  /// where `p_i` is the probability of the `i`-th word, which is lower than `log2(n)` unless all
+  /// words have the same length. With [Passphraser::unique_words], the reduction for sampling
+  /// without replacement is estimated as if words were picked uniformly.
+  pub fn prefer_short(&mut self, prefer_short: bool) -> &mut Self {
+    self.prefer_short = prefer_short;
+    self.counts.take();
+    self
+  }
 
-/// Capitalizes the first char of given string.
-pub(crate) fn to_capitalized(s: &str) -> String {
-  let mut chars = s.chars();
+  /// Set the character classes that passphrases must contain. See [Policy] for how each class is
+  /// injected.
+  pub fn policy(&mut self, policy: Policy) -> &mut Self {
+    self.policy = policy;
+    self
+  }
 
-  match chars.next() {
-    | None => String::new(),
-    | Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+  /// Set the number of dice rolled per word, e.g. 4 for the EFF short wordlist.
+  pub fn dice_count(&mut self, dice_count: usize) -> &mut Self {
+    self.dice_count = dice_count;
+    self.counts.take();
+    self
   }
-}
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+  /// Set the number of faces each die has. With [IndexScheme::Concatenated] indices are built by
+  /// concatenating rolled numbers, so this should not exceed 9.
+  pub fn dice_faces(&mut self, dice_faces: usize) -> &mut Self {
+    self.dice_faces = dice_faces;
+    self.counts.take();
+    self
+  }
 
-  #[test]
-  #[should_panic]
-  fn test_roll_dice() {
-    roll_dice(6, 5, 0, 0);
-    roll_dice(6, 0, 0, 0);
+  /// Set the scheme of building wordlist indices from rolls, e.g. [IndexScheme::Positional] for
+  /// wordlists indexed from 0.
+  pub fn index_scheme(&mut self, index_scheme: IndexScheme) -> &mut Self {
+    self.index_scheme = index_scheme;
+    self.counts.take();
+    self
+  }
+
+  /// Set the seed to use for rolling dice. The same seed, wordlist and configuration always yield
+  /// the same passphrase, so this should only be used for testing or deterministic generation.
+  pub fn seed(&mut self, seed: u64) -> &mut Self {
+    self.seed = Some(seed);
+    self
+  }
+
+  /// Set the number of random digits to append to the passphrase. This is meant for complying with
+  /// password policies that require digits, not as a primary source of entropy, though the digits'
+  /// entropy is accounted for.
+  pub fn numbers(&mut self, numbers: usize) -> &mut Self {
+    self.numbers = numbers;
+    self
+  }
+
+  /// Set the symbols to pick from for inserting one random symbol after a random word. Like digits,
+  /// this is meant for complying with password policies, and only the entropy of picking a symbol
+  /// is accounted for. Repeated symbols are ignored.
+  pub fn symbols(&mut self, symbols: &str) -> &mut Self {
+    let symbols = symbols.chars().fold(Vec::new(), |mut acc, symbol| {
+      if !acc.contains(&symbol) {
+        acc.push(symbol);
+      }
+
+      acc
+    });
+
+    self.symbols = Some(symbols).filter(|symbols| !symbols.is_empty());
+    self
+  }
+
+  /// Set substrings that words must not contain. Since excluded words leave gaps in the wordlist,
+  /// the remaining words are picked uniformly instead of by rolled indices, and entropy is
+  /// calculated using their number.
+  pub fn exclude(&mut self, exclude: &[String]) -> &mut Self {
+    self.exclude = exclude.to_vec();
+    self.counts.take();
+    self
+  }
+
+  /// Set words that must not appear in passphrases, e.g. the most common passwords. Words are
+  /// compared case-insensitively, and banned words are re-rolled, so the number of possibilities
+  /// and thus entropy shrink by the number of banned words in the wordlist. Words of given dice
+  /// rolls aren't re-rolled.
+  pub fn banlist(&mut self, banlist: &[String]) -> &mut Self {
+    self.banlist = Arc::new(banlist.iter().map(|word| word.to_lowercase()).collect());
+    self.counts.take();
+    self
+  }
+
+  /// Set the minimum entropy in bits, so that the number of words is calculated during generation
+  /// using [Passphraser::for_entropy] instead of being set explicitly.
+  pub fn min_entropy(&mut self, bits: f32) -> &mut Self {
+    self.min_entropy = Some(bits);
+    self
+  }
+
+  /// Same as [Passphraser::min_entropy]. The target is reported in [Entropy::target] of generated
+  /// passphrases along with the achieved entropy, see [Entropy::overshoot]. With
+  /// [Passphraser::unique_words], words are counted one at a time, adding `log2(N - i)` bits for
+  /// the `i`-th word, until the target is met, since each next word adds less entropy.
+  pub fn target_bits(&mut self, bits: f32) -> &mut Self {
+    self.min_entropy(bits)
+  }
+
+  /// Set the minimum number of chars of the formatted passphrase, e.g. to comply with password
+  /// policies. Words are added one by one until the passphrase is long enough, but no more than
+  /// [Passphraser::MIN_CHARS_WORDS_MAX] in total. Entropy of generated passphrases accounts for the
+  /// added words, unlike [Passphraser::expected_entropy].
+  pub fn min_chars(&mut self, min_chars: usize) -> &mut Self {
+    self.min_chars = min_chars;
+    self
+  }
+
+  /// Calculates the minimum number of words picked from a wordlist of the given size, so that the
+  /// entropy of words is at least `bits`. The result is always rounded up, but capped at
+  /// [Passphraser::MIN_ENTROPY_WORDS_MAX]. Returns 0 if `bits` is not positive, or if the target
+  /// can't be reached because the wordlist has less than 2 words.
+  pub fn for_entropy(bits: f32, wordlist_size: usize) -> usize {
+    let per_word = calc_entropy(wordlist_size, 1);
+
+    if bits.is_nan() || bits <= 0.0 || per_word <= 0.0 {
+      return 0;
+    }
+
+    let length = (bits / per_word)
+      .ceil()
+      .min(Self::MIN_ENTROPY_WORDS_MAX as f32) as usize;
+
+    // Guarding against floating point errors in the division above.
+    if length < Self::MIN_ENTROPY_WORDS_MAX && calc_entropy(wordlist_size, length) < bits {
+      length + 1
+    } else {
+      length
+    }
+  }
+
+  /// Checks that the wordlist contains every index that can be rolled with the configured dice
+  /// exactly once. Plain wordlists have no indices, so they are always valid.
+  pub fn validate(&self) -> Result<(), WordlistError> {
+    if self.is_indexed() {
+      validate_indices(&self.wordlist, self.expected_indices())
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Finds words that occur in the wordlist more than once. See [duplicate_words].
+  pub fn duplicate_words(&self) -> Vec<(String, Vec<usize>)> {
+    duplicate_words(&self.wordlist)
+  }
+
+  /// Calculates statistics of the wordlist. See [wordlist_stats].
+  pub fn wordlist_stats(&self) -> WordlistStats {
+    wordlist_stats(&self.wordlist)
+  }
+
+  /// Returns the number of words of passphrases with the current configuration, calculated from the
+  /// minimum entropy if it's set. Words added to reach the minimum number of chars aren't counted.
+  pub fn word_count(&self) -> usize {
+    self.word_count_for(self.possibilities())
+  }
+
+  /// Calculates entropy of passphrases with the current configuration without generating one, e.g.
+  /// to show it while the configuration is being tweaked. Excluded words are accounted for.
+  pub fn expected_entropy(&self) -> Entropy {
+    self.entropy()
+  }
+
+  /// Computes the check word for given passphrase words. Words of the wordlist are numbered from 0
+  /// in the order of their indices, or in the order they appear in if the wordlist is plain. The
+  /// check word is then the word numbered with the sum of numbers of the given words modulo the
+  /// wordlist size. Returns [None] if any of the given words is not in the wordlist.
+  pub fn check_word(&self, words: &[String]) -> Option<String> {
+    let list = self.ordered_words();
+    let mut positions = HashMap::new();
+
+    for (position, word) in list.iter().enumerate() {
+      positions.entry(*word).or_insert(position);
+    }
+
+    let sum = words.iter().try_fold(0, |acc, word| {
+      positions
+        .get(word.as_str())
+        .map(|position| (acc + position) % list.len())
+    })?;
+
+    Some(list[sum].to_string())
+  }
+
+  /// Roll dice, generate passphrase words, calculate entropy and return a [Passphrase]. Uses a
+  /// seeded RNG if the seed is set, or the thread-local RNG otherwise.
+  ///
+  /// Rolled indices missing from the wordlist are silently skipped, so the passphrase may contain
+  /// fewer words than requested. Use [Passphraser::generate_checked] to get an error instead.
+  #[cfg(feature = "thread-rng")]
+  pub fn generate(&self) -> Passphrase {
+    self.generate_with(&mut *self.rng())
+  }
+
+  /// Same as [Passphraser::generate], but returns an error if any rolled index is missing from the
+  /// wordlist, or if the length is zero and no minimum entropy is set.
+  #[cfg(feature = "thread-rng")]
+  pub fn generate_checked(&self) -> Result<Passphrase, GenerateError> {
+    self.generate_checked_with(&mut *self.rng())
+  }
+
+  /// Generates `n` distinct passphrases. See [Passphraser::generate_many_with].
+  #[cfg(feature = "thread-rng")]
+  pub fn generate_many(&self, n: usize) -> Result<Vec<Passphrase>, GenerateError> {
+    self.generate_many_with(n, &mut *self.rng())
+  }
+
+  /// Same as [Passphraser::generate_many], but also measures how long generation took, e.g. to
+  /// compare wordlists and configurations.
+  #[cfg(feature = "thread-rng")]
+  pub fn generate_many_timed(
+    &self,
+    n: usize,
+  ) -> Result<(Vec<Passphrase>, GenerationStats), GenerateError> {
+    let started = std::time::Instant::now();
+    let passphrases = self.generate_many(n)?;
+    let stats = GenerationStats::new(&passphrases, started.elapsed());
+
+    Ok((passphrases, stats))
+  }
+
+  /// Returns an infinite iterator lazily generating passphrases with the current configuration.
+  /// Unlike repeated [Passphraser::generate] calls, a seeded iterator doesn't yield the same
+  /// passphrase over and over, since a single RNG is used for all of them.
+  #[cfg(feature = "thread-rng")]
+  pub fn iter(&self) -> impl Iterator<Item = Passphrase> + '_ {
+    let mut rng = self.rng();
+    let possibilities = self.possibilities();
+    let origin = self.origin();
+
+    std::iter::repeat_with(move || self.generate_unchecked(&mut *rng, possibilities, &origin))
+  }
+
+  /// Same as `Passphraser::generate`, but uses the given random number generator, e.g. `OsRng`.
+  /// Unlike `Passphraser::generate`, it's available without the `thread-rng` feature, e.g. on
+  /// `wasm32-unknown-unknown`. The seed set with [Passphraser::seed] is ignored.
+  pub fn generate_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Passphrase {
+    self.generate_unchecked(rng, self.possibilities(), &self.origin())
+  }
+
+  /// Generates `n` passphrases into the given buffer, e.g. for bulk generation in research. The
+  /// buffer can be reused between batches to avoid reallocating it.
+  pub fn generate_batch_into<R: Rng + ?Sized>(
+    &self,
+    out: &mut Vec<Passphrase>,
+    n: usize,
+    rng: &mut R,
+  ) {
+    let possibilities = self.possibilities();
+    let origin = self.origin();
+
+    out.reserve(n);
+    out.extend((0..n).map(|_| self.generate_unchecked(rng, possibilities, &origin)));
+  }
+
+  /// Generates `n` distinct passphrases using the given random number generator, e.g. to offer
+  /// several candidates to pick from. Passphrases are compared formatted, and duplicates are
+  /// re-rolled. Returns an error if generation fails, or if a new passphrase isn't found within
+  /// [Passphraser::DUPLICATE_RETRIES_MAX] re-rolls in a row, e.g. because the wordlist is tiny.
+  pub fn generate_many_with<R: Rng + ?Sized>(
+    &self,
+    n: usize,
+    rng: &mut R,
+  ) -> Result<Vec<Passphrase>, GenerateError> {
+    self.check_length()?;
+
+    let possibilities = self.possibilities();
+    let origin = self.origin();
+    let mut seen = HashSet::new();
+    let mut passphrases = Vec::with_capacity(n);
+    let mut retries = 0;
+
+    while passphrases.len() < n {
+      let passphrase = self.generate_words(rng, true, possibilities, &origin)?;
+
+      if seen.insert(passphrase.format()) {
+        passphrases.push(passphrase);
+        retries = 0;
+      } else if retries < Self::DUPLICATE_RETRIES_MAX {
+        retries += 1;
+      } else {
+        return Err(GenerateError::TooFewCandidates {
+          requested: n,
+          generated: passphrases.len(),
+        });
+      }
+    }
+
+    Ok(passphrases)
+  }
+
+  /// Same as `Passphraser::generate_checked`, but uses the given random number generator.
+  pub fn generate_checked_with<R: Rng + ?Sized>(
+    &self,
+    rng: &mut R,
+  ) -> Result<Passphrase, GenerateError> {
+    self.check_length()?;
+    self.generate_words(rng, true, self.possibilities(), &self.origin())
+  }
+
+  /// Generates a passphrase from given dice rolls, e.g. rolled with physical dice and parsed with
+  /// [Passphraser::parse_rolls], instead of rolling dice. The number of words is the number of
+  /// rolls, and excluded words are not taken into account. Digits and symbols, if any, are still
+  /// generated randomly.
+  #[cfg(feature = "thread-rng")]
+  pub fn generate_from_rolls(&self, rolls: Vec<Vec<usize>>) -> Result<Passphrase, GenerateError> {
+    self.generate_from_rolls_with(&mut *self.rng(), rolls)
+  }
+
+  /// Derives a passphrase deterministically from a secret key and a salt, so that the same key,
+  /// salt and configuration always give the same passphrase. The scheme is as follows:
+  ///
+  /// 1. HKDF-SHA256 is applied with the key as input keying material, the salt as salt and
+  ///    [Passphraser::KDF_INFO] as info, expanding them into a 32-byte seed.
+  /// 2. ChaCha20 is seeded with it, as implemented by `rand_chacha`.
+  /// 3. The passphrase is generated with it as in [Passphraser::generate_with].
+  ///
+  /// The key should have enough entropy on its own, since HKDF doesn't slow down brute force.
+  #[cfg(feature = "kdf")]
+  pub fn from_key(&self, key: &[u8], salt: &[u8]) -> Passphrase {
+    use rand::SeedableRng;
+
+    let mut seed = [0; 32];
+
+    hkdf::Hkdf::<sha2::Sha256>::new(Some(salt), key)
+      .expand(Self::KDF_INFO, &mut seed)
+      .expect("32 bytes is a valid length for HKDF-SHA256");
+
+    self.generate_with(&mut rand_chacha::ChaCha20Rng::from_seed(seed))
+  }
+
+  /// Same as `Passphraser::generate_from_rolls`, but uses the given random number generator.
+  pub fn generate_from_rolls_with<R: Rng + ?Sized>(
+    &self,
+    rng: &mut R,
+    rolls: Vec<Vec<usize>>,
+  ) -> Result<Passphrase, GenerateError> {
+    if let Some(roll) = rolls.iter().find(|roll| !self.is_rollable(roll)) {
+      return Err(GenerateError::InvalidRoll(roll.clone()));
+    }
+
+    let words = rolls
+      .iter()
+      .map(|roll| self.lookup(roll))
+      .collect::<Result<_, _>>()?;
+
+    let entropy = self.entropy_for(self.roll_possibilities(), rolls.len());
+
+    Ok(self.assemble(rng, words, rolls, entropy, &self.origin()))
+  }
+
+  /// Parses compact dice rolls made by [Passphrase::rolls_compact] using the configured dice, e.g.
+  /// to pass them to [Passphraser::generate_from_rolls_with].
+  pub fn parse_rolls_compact(&self, compact: &str) -> Result<Vec<Vec<usize>>, RollsError> {
+    let digits = compact.chars().collect::<Vec<_>>();
+
+    digits
+      .chunks(self.roll_len())
+      .enumerate()
+      .map(|(index, chunk)| {
+        if chunk.len() != self.roll_len() {
+          return Err(RollsError::Length {
+            position: index + 1,
+            group: chunk.iter().collect(),
+            expected: self.roll_len(),
+          });
+        }
+
+        chunk
+          .iter()
+          .enumerate()
+          .map(|(die, &digit)| {
+            let faces = self.faces_of(die);
+
+            digit
+              .to_digit(36)
+              .map(|n| n as usize + 1)
+              .filter(|&n| n <= faces)
+              .ok_or(RollsError::InvalidDigit { digit, faces })
+          })
+          .collect()
+      })
+      .collect()
+  }
+
+  /// Generates a passphrase from compact dice rolls made by [Passphrase::rolls_compact], e.g. to
+  /// restore a passphrase from a backup. Words are the same if the wordlist and dice are the same,
+  /// while digits and symbols, if any, are generated anew.
+  #[cfg(feature = "thread-rng")]
+  pub fn from_rolls_compact(&self, compact: &str) -> Result<Passphrase, DicewareError> {
+    let rolls = self.parse_rolls_compact(compact)?;
+
+    Ok(self.generate_from_rolls(rolls)?)
+  }
+
+  /// Parses dice rolls using the configured dice. See [parse_rolls]. If wordlists are merged, each
+  /// group starts with the die selecting the list.
+  pub fn parse_rolls(&self, input: &str) -> Result<Vec<Vec<usize>>, RollsError> {
+    parse_groups(input, self.roll_len(), |die| self.faces_of(die))
+  }
+
+  /// Returns an error if the length is zero and neither a minimum entropy nor a template is set.
+  fn check_length(&self) -> Result<(), GenerateError> {
+    let templated = matches!(self.preset, Preset::Template(_));
+
+    if self.length == 0 && self.min_entropy.is_none() && !templated {
+      return Err(GenerateError::ZeroLength);
+    }
+
+    Ok(())
+  }
+
+  /// Same as [Passphraser::generate_words], but skips words that can't be picked.
+  fn generate_unchecked<R: Rng + ?Sized>(
+    &self,
+    rng: &mut R,
+    possibilities: usize,
+    origin: &Origin,
+  ) -> Passphrase {
+    match self.generate_words(rng, false, possibilities, origin) {
+      | Ok(passphrase) => passphrase,
+      | Err(_) => unreachable!("errors are skipped in unchecked generation"),
+    }
+  }
+
+  /// Picks words and builds a [Passphrase], adding words until it has the minimum number of chars.
+  /// Words that can't be picked are either skipped, or returned as an error if `checked` is set.
+  /// The number of possibilities and the origin are passed in, so that they're looked up once and
+  /// shared for many passphrases.
+  fn generate_words<R: Rng + ?Sized>(
+    &self,
+    rng: &mut R,
+    checked: bool,
+    possibilities: usize,
+    origin: &Origin,
+  ) -> Result<Passphrase, GenerateError> {
+    let mut words = Vec::new();
+    let mut rolls = Vec::new();
+    let mut count = self.word_count_for(possibilities);
+    let mut picked = 0;
+
+    let available = if self.unique_words {
+      possibilities
+    } else {
+      usize::MAX
+    };
+
+    if count > available {
+      if checked {
+        return Err(GenerateError::TooFewWords {
+          length: count,
+          words: available,
+        });
+      }
+
+      count = available;
+    }
+
+    loop {
+      let picks = if self.unique_words {
+        self.pick_unique(rng, count - picked, &words)
+      } else {
+        self.pick(rng, count - picked)
+      };
+
+      for pick in picks {
+        match pick {
+          | Ok((word, roll)) => {
+            words.push(word);
+            rolls.extend(roll);
+          },
+          | Err(err) if checked => return Err(err),
+          | Err(_) => {},
+        }
+      }
+
+      picked = count;
+
+      let last = count >= Self::MIN_CHARS_WORDS_MAX || count >= available;
+
+      if last || self.formatted_len(&words) >= self.min_chars {
+        let entropy = self.entropy_for(possibilities, count);
+        let passphrase = self.assemble(rng, words.clone(), rolls.clone(), entropy, origin);
+
+        // The check word and random delimiters are only known once assembled, so the length is
+        // checked again in case they turned out shorter than estimated.
+        if last || passphrase.format().chars().count() >= self.min_chars {
+          return Ok(passphrase);
+        }
+      }
+
+      count += 1;
+    }
+  }
+
+  /// Picks the given number of words along with their rolls. Words are looked up by rolled indices,
+  /// unless the wordlist is plain or some words are excluded, in which case they are picked
+  /// uniformly, without rolls, or weighted toward shorter ones if [Passphraser::prefer_short] is
+  /// set.
+  fn pick<R: Rng + ?Sized>(&self, rng: &mut R, count: usize) -> Vec<Result<Pick, GenerateError>> {
+    if let Some(pool) = self.pool() {
+      let cumulative = self.prefer_short.then(|| {
+        short_weights(&pool)
+          .scan(0.0, |total, weight| {
+            *total += weight;
+            Some(*total)
+          })
+          .collect::<Vec<_>>()
+      });
+
+      return (0..count)
+        .map(|_| {
+          if pool.is_empty() {
+            return Err(GenerateError::EmptyWordlist);
+          }
+
+          let position = match cumulative.as_deref() {
+            | Some(sums @ [.., total]) => {
+              let target = rng.gen_range(0.0..*total);
+              sums
+                .partition_point(|&sum| sum <= target)
+                .min(pool.len() - 1)
+            },
+            | _ => rng.gen_range(0..pool.len()),
+          };
+
+          Ok((pool[position].to_string(), None))
+        })
+        .collect();
+    }
+
+    if !self.banlist.is_empty() && self.roll_possibilities() == 0 {
+      return (0..count)
+        .map(|_| Err(GenerateError::EmptyWordlist))
+        .collect();
+    }
+
+    self
+      .roll(rng, count)
+      .into_iter()
+      .map(|roll| self.lookup_allowed(rng, roll))
+      .collect()
+  }
+
+  /// Looks up the word for given roll, re-rolling while the word is banned, up to
+  /// [Passphraser::REROLLS_MAX] times.
+  fn lookup_allowed<R: Rng + ?Sized>(
+    &self,
+    rng: &mut R,
+    mut roll: Vec<usize>,
+  ) -> Result<Pick, GenerateError> {
+    for _ in 0..=Self::REROLLS_MAX {
+      let word = self.lookup(&roll)?;
+
+      if !self.is_banned(&word) {
+        return Ok((word, Some(roll)));
+      }
+
+      roll = self.roll(rng, 1).remove(0);
+    }
+
+    Err(GenerateError::RerollsExhausted)
+  }
+
+  /// Returns whether the word is banned, see [Passphraser::banlist].
+  fn is_banned(&self, word: &str) -> bool {
+    if self.banlist.is_empty() {
+      false
+    } else if word.chars().any(char::is_uppercase) {
+      self.banlist.contains(&word.to_lowercase())
+    } else {
+      self.banlist.contains(word)
+    }
+  }
+
+  /// Same as [Passphraser::pick], but re-rolls words that are among the already picked ones or
+  /// were picked earlier in this call, giving up after [Passphraser::REROLLS_MAX] re-rolls in a
+  /// row.
+  fn pick_unique<R: Rng + ?Sized>(
+    &self,
+    rng: &mut R,
+    count: usize,
+    picked: &[String],
+  ) -> Vec<Result<Pick, GenerateError>> {
+    let mut seen = picked.iter().cloned().collect::<HashSet<_>>();
+    let mut picks = Vec::with_capacity(count);
+    let mut rerolls = 0;
+
+    while picks.len() < count {
+      for pick in self.pick(rng, count - picks.len()) {
+        match &pick {
+          | Ok((word, _)) if !seen.insert(word.clone()) => rerolls += 1,
+          | _ => {
+            picks.push(pick);
+            rerolls = 0;
+          },
+        }
+      }
+
+      if rerolls > Self::REROLLS_MAX {
+        picks.resize_with(count, || Err(GenerateError::RerollsExhausted));
+      }
+    }
+
+    picks
+  }
+
+  /// Looks up the word for the index built from given roll.
+  fn lookup(&self, roll: &[usize]) -> Result<String, GenerateError> {
+    let index = self.index_of(roll);
+
+    self
+      .entries
+      .get(&index)
+      .cloned()
+      .ok_or(GenerateError::MissingIndex(index))
+  }
+
+  /// Rolls dice for the given number of words.
+  fn roll<R: Rng + ?Sized>(&self, rng: &mut R, count: usize) -> Vec<Vec<usize>> {
+    let mut rolls = roll_dice_with(rng, count, self.dice_count, 1, self.dice_faces + 1);
+
+    if self.lists > 1 {
+      for roll in &mut rolls {
+        roll.insert(0, rng.gen_range(1..=self.lists));
+      }
+    }
+
+    rolls
+  }
+
+  /// Builds the index for given roll, prefixed with the list number if wordlists are merged.
+  fn index_of(&self, roll: &[usize]) -> usize {
+    match roll.split_first() {
+      | Some((list, rest)) if self.lists > 1 => {
+        list * self.list_base() + self.index_scheme.index(rest, self.dice_faces)
+      },
+      | _ => self.index_scheme.index(roll, self.dice_faces),
+    }
+  }
+
+  /// Returns the multiplier of list numbers prefixing indices of merged wordlists.
+  fn list_base(&self) -> usize {
+    10usize.pow(self.index_scheme.digits(self.dice_count, self.dice_faces))
+  }
+
+  /// Returns the number of faces of the die at the given position within a roll, which is the
+  /// number of lists for the die selecting a merged wordlist.
+  fn faces_of(&self, die: usize) -> usize {
+    if self.lists > 1 && die == 0 {
+      self.lists
+    } else {
+      self.dice_faces
+    }
+  }
+
+  /// Returns whether the roll can be made with the configured dice, i.e. has a number within the
+  /// faces of each die.
+  fn is_rollable(&self, roll: &[usize]) -> bool {
+    roll.len() == self.roll_len()
+      && roll
+        .iter()
+        .enumerate()
+        .all(|(die, n)| (1..=self.faces_of(die)).contains(n))
+  }
+
+  /// Returns the number of dice rolled per word, including the die selecting a merged wordlist.
+  fn roll_len(&self) -> usize {
+    if self.lists > 1 {
+      self.dice_count + 1
+    } else {
+      self.dice_count
+    }
+  }
+
+  /// Returns all indices that can be rolled, prefixed with list numbers if wordlists are merged.
+  fn expected_indices(&self) -> Vec<usize> {
+    let indices = self.index_scheme.indices(self.dice_count, self.dice_faces);
+
+    if self.lists == 1 {
+      return indices;
+    }
+
+    let base = self.list_base();
+
+    (1..=self.lists)
+      .flat_map(|list| indices.iter().map(move |index| list * base + index))
+      .collect()
+  }
+
+  /// Returns entropy per word of merged wordlists, see [Passphraser::wordlists].
+  fn merged_bits_per_word(&self) -> f32 {
+    let base = self.list_base();
+    let rolls = self.dice_faces.pow(self.dice_count as u32);
+    let mut words = vec![HashSet::new(); self.lists];
+
+    for (index, word) in self.entries.iter() {
+      if let Some(list) = (index / base)
+        .checked_sub(1)
+        .and_then(|list| words.get_mut(list))
+      {
+        list.insert(word);
+      }
+    }
+
+    let within = words
+      .iter()
+      .map(|list| calc_entropy(list.len().clamp(1, rolls), 1))
+      .sum::<f32>();
+
+    calc_entropy(self.lists, 1) + within / self.lists as f32
+  }
+
+  /// Returns distinct words to pick from without rolls if the wordlist is plain, i.e. has no
+  /// indices, if any words are excluded, or if shorter words are preferred, so that picks are
+  /// uniform over the words entropy is calculated with. Words of indexed wordlists are ordered by
+  /// their indices.
+  fn pool(&self) -> Option<Vec<&str>> {
+    if self.is_indexed() && self.exclude.is_empty() && !self.prefer_short {
+      return None;
+    }
+
+    let mut seen = HashSet::new();
+    let words = self
+      .ordered_words()
+      .into_iter()
+      .filter(|word| !self.exclude.iter().any(|part| word.contains(part)))
+      .filter(|word| !self.is_banned(word))
+      .filter(|word| seen.insert(*word))
+      .collect();
+
+    Some(words)
+  }
+
+  /// Returns entropy per word of picks weighted toward shorter words, see
+  /// [Passphraser::prefer_short].
+  fn short_bits_per_word(&self) -> f32 {
+    let pool = self.pool().unwrap_or_default();
+    let total = short_weights(&pool).sum::<f64>();
+
+    let bits = short_weights(&pool)
+      .map(|weight| weight / total)
+      .map(|p| -p * p.log2())
+      .sum::<f64>();
+
+    bits as f32
+  }
+
+  /// Returns all words of the wordlist, ordered by their indices if the wordlist is indexed, or in
+  /// the order they appear in otherwise.
+  fn ordered_words(&self) -> Vec<&str> {
+    if self.is_indexed() {
+      let mut entries = self.entries.iter().collect::<Vec<_>>();
+      entries.sort_unstable();
+
+      entries.into_iter().map(|(_, word)| word.as_str()).collect()
+    } else {
+      self
+        .wordlist
+        .iter()
+        .map(|line| line.trim())
+        .filter(|word| !word.is_empty() && !is_comment(word))
+        .collect()
+    }
+  }
+
+  /// Returns a copy of the builder to keep in generated passphrases, see [Origin].
+  fn origin(&self) -> Origin {
+    Origin(Arc::new(self.clone()))
+  }
+
+  /// Returns whether the wordlist has `<index> <word>` pairs. Otherwise it's considered plain, with
+  /// one word per line.
+  fn is_indexed(&self) -> bool {
+    !self.entries.is_empty()
+  }
+
+  /// Estimates the number of chars of the passphrase formatted from given words without assembling
+  /// it, counting delimiters, digits, the symbol, the prefix and the suffix. The check word isn't
+  /// counted and random delimiters are assumed to be the shortest ones, since they're only known
+  /// once assembled.
+  fn formatted_len(&self, words: &[String]) -> usize {
+    let len = |text: &str| text.chars().count();
+    let joins = (words.len() + usize::from(self.checksum)).saturating_sub(1);
+
+    let delimiters = match (&self.preset, &self.delimiter) {
+      | (Preset::Template(_), _) => 0,
+      | (Preset::Delimiters(delimiters), None) if !delimiters.is_empty() => {
+        delimiters
+          .iter()
+          .cycle()
+          .take(joins)
+          .map(|delimiter| len(delimiter))
+          .sum()
+      },
+      | (preset, delimiter) => {
+        let delimiter = if self.random_delimiters_applied() {
+          self
+            .random_delimiters
+            .iter()
+            .map(|delimiter| len(delimiter))
+            .min()
+        } else if let Some(delimiter) = delimiter {
+          Some(len(delimiter))
+        } else {
+          preset
+            .format_options()
+            .map(|options| len(&options.delimiter))
+        };
+
+        let group_delimiter = self
+          .group_delimiter
+          .as_deref()
+          .unwrap_or(Passphrase::DELIM_DEFAULT);
+
+        let group_joins = joins.checked_div(self.group_size).unwrap_or(0);
+        let delimiter = delimiter.unwrap_or(Passphrase::DELIM_DEFAULT.len());
+
+        (joins - group_joins) * delimiter + group_joins * len(group_delimiter)
+      },
+    };
+
+    let symbol = usize::from(self.symbol_set().is_some() && !words.is_empty());
+
+    words.iter().map(|word| len(word)).sum::<usize>()
+      + delimiters
+      + self.digit_count()
+      + symbol
+      + len(&self.prefix)
+      + len(&self.suffix)
+  }
+
+  /// Builds a [Passphrase] from picked words, generating the remaining random parts.
+  fn assemble<R: Rng + ?Sized>(
+    &self,
+    rng: &mut R,
+    mut words: Vec<String>,
+    rolls: Vec<Vec<usize>>,
+    entropy: Entropy,
+    origin: &Origin,
+  ) -> Passphrase {
+    let numbers = random_digits(rng, self.digit_count());
+
+    let symbol = match &self.symbol_set() {
+      | Some(symbols) if !words.is_empty() => {
+        let position = rng.gen_range(0..words.len());
+        let symbol = symbols[rng.gen_range(0..symbols.len())];
+
+        Some((position, symbol))
+      },
+      | _ => None,
+    };
+
+    let casing = if matches!(self.preset, Preset::RandomCase) {
+      words.iter().map(|_| rng.gen_bool(0.5)).collect()
+    } else {
+      Vec::new()
+    };
+
+    // Appending the check word last, so that it's never picked for the symbol or random casing.
+    let check_word = if self.checksum && !words.is_empty() {
+      self.check_word(&words)
+    } else {
+      None
+    };
+
+    let checksum = check_word.is_some();
+    words.extend(check_word);
+
+    let delimiters = if self.random_delimiters_applied() {
+      (1..words.len())
+        .map(|_| {
+          Some(self.random_delimiters[rng.gen_range(0..self.random_delimiters.len())].clone())
+        })
+        .collect()
+    } else {
+      Vec::new()
+    };
+
+    let indices = rolls.iter().map(|roll| self.index_of(roll)).collect();
+
+    let mut passphrase = Passphrase {
+      words,
+      rolls,
+      indices,
+      numbers,
+      symbol,
+      casing,
+      capitalize: self.capitalize,
+      normalize_case: self.normalize_case,
+      checksum,
+      uppercase: self.policy.uppercase,
+      delimiter: self.delimiter.clone(),
+      group_size: self.group_size,
+      group_delimiter: self.group_delimiter.clone(),
+      delimiters,
+      prefix: self.prefix.clone(),
+      suffix: self.suffix.clone(),
+      preset: self.preset.clone(),
+      entropy,
+      origin: Some(origin.clone()),
+    };
+
+    if self.shuffle {
+      passphrase.shuffle(rng);
+    }
+
+    passphrase
+  }
+
+  /// Calculates entropy of the passphrase with the current configuration.
+  fn entropy(&self) -> Entropy {
+    let possibilities = self.possibilities();
+
+    self.entropy_for(possibilities, self.word_count_for(possibilities))
+  }
+
+  /// Calculates entropy of the passphrase with the given number of words, each picked out of the
+  /// given number of possibilities.
+  fn entropy_for(&self, possibilities: usize, word_count: usize) -> Entropy {
+    let mut entropy = Entropy::new(possibilities, word_count);
+
+    if let Some(bits_per_word) = self.counts().bits_per_word {
+      entropy.entropy = bits_per_word * word_count as f32;
+    }
+
+    if self.unique_words {
+      let replaced = calc_entropy(possibilities, word_count);
+      let unique = calc_entropy_no_replacement(possibilities, word_count);
+
+      entropy.entropy -= replaced - unique;
+    }
+
+    entropy.entropy += calc_entropy(10, self.digit_count());
+    entropy.target = self.min_entropy;
+
+    if matches!(self.preset, Preset::RandomCase) {
+      entropy.entropy += word_count as f32;
+    }
+
+    if let Some(symbols) = &self.symbol_set() {
+      entropy.entropy += calc_entropy(symbols.len(), 1);
+    }
+
+    if self.random_delimiters_applied() {
+      let joins = (word_count + usize::from(self.checksum)).saturating_sub(1);
+      let group_joins = joins.checked_div(self.group_size).unwrap_or(0);
+
+      entropy.entropy += calc_entropy(self.random_delimiters.len(), joins - group_joins);
+    }
+
+    entropy
+  }
+
+  /// Returns whether random delimiters are set and the preset joins words with them.
+  fn random_delimiters_applied(&self) -> bool {
+    let joined = match &self.preset {
+      | Preset::Template(_) => false,
+      | Preset::Delimiters(delimiters) => delimiters.is_empty() || self.delimiter.is_some(),
+      | _ => true,
+    };
+
+    joined && !self.random_delimiters.is_empty()
+  }
+
+  /// Returns the number of digits to append, which is at least 1 if the policy requires a digit.
+  fn digit_count(&self) -> usize {
+    let numbers = match &self.preset {
+      | Preset::Template(template) => template.digit_count() + self.numbers,
+      | _ => self.numbers,
+    };
+
+    if self.policy.digit {
+      numbers.max(1)
+    } else {
+      numbers
+    }
+  }
+
+  /// Returns symbols to pick from, falling back to [Passphraser::SYMBOLS_DEFAULT] if the policy
+  /// requires a symbol, but no symbols are set.
+  fn symbol_set(&self) -> Option<Vec<char>> {
+    match &self.symbols {
+      | Some(symbols) => Some(symbols.clone()),
+      | None if self.policy.symbol => Some(Self::SYMBOLS_DEFAULT.chars().collect()),
+      | None => None,
+    }
+  }
+
+  /// Returns a seeded RNG if the seed is set, or the thread-local RNG otherwise.
+  #[cfg(feature = "thread-rng")]
+  fn rng(&self) -> Box<dyn RngCore> {
+    if let Some(seed) = self.seed {
+      Box::new(StdRng::seed_from_u64(seed))
+    } else {
+      Box::new(rand::thread_rng())
+    }
+  }
+
+  /// Returns the number of words to generate, calculating it from the minimum entropy if it's set.
+  fn word_count_for(&self, possibilities: usize) -> usize {
+    if let Preset::Template(template) = &self.preset {
+      return template.word_count();
+    }
+
+    match self.min_entropy {
+      | Some(bits) if self.prefer_short => {
+        let per_word = self.counts().bits_per_word.unwrap_or_default();
+
+        if bits.is_nan() || bits <= 0.0 || per_word <= 0.0 {
+          0
+        } else {
+          (bits / per_word)
+            .ceil()
+            .min(Self::MIN_ENTROPY_WORDS_MAX as f32) as usize
+        }
+      },
+      | Some(bits) if self.unique_words => {
+        if bits.is_nan() || bits <= 0.0 || possibilities < 2 {
+          return 0;
+        }
+
+        let mut total = 0.0;
+        let mut count = 0;
+
+        while total < bits && count < possibilities && count < Self::MIN_ENTROPY_WORDS_MAX {
+          total += calc_entropy(possibilities - count, 1);
+          count += 1;
+        }
+
+        count
+      },
+      | Some(bits) => Self::for_entropy(bits, possibilities),
+      | None => self.length,
+    }
+  }
+
+  /// Returns the number of unique words that can actually be picked, which is limited both by the
+  /// number of parsed words and by the number of distinct dice rolls, or by the number of words
+  /// left after exclusion. Blank lines, unparseable lines and duplicate words are not counted.
+  fn possibilities(&self) -> usize {
+    self.counts().possibilities
+  }
+
+  /// Returns the number of unique words that can be looked up by rolled indices. Words at indices
+  /// that can't be rolled with the configured dice are not counted.
+  fn roll_possibilities(&self) -> usize {
+    self.counts().roll_possibilities
+  }
+
+  /// Returns counts derived from the wordlist, counting them on first use. See [Counts].
+  fn counts(&self) -> &Counts {
+    self.counts.get_or_init(|| {
+      let roll_possibilities = self
+        .expected_indices()
+        .into_iter()
+        .filter_map(|index| self.entries.get(&index))
+        .filter(|word| !self.is_banned(word))
+        .collect::<HashSet<_>>()
+        .len();
+
+      let possibilities = match self.pool() {
+        | Some(pool) => pool.into_iter().collect::<HashSet<_>>().len(),
+        | None => roll_possibilities,
+      };
+
+      let bits_per_word = if self.prefer_short {
+        Some(self.short_bits_per_word())
+      } else if self.lists > 1 && self.is_indexed() && self.exclude.is_empty() {
+        Some(self.merged_bits_per_word())
+      } else {
+        None
+      };
+
+      Counts {
+        possibilities,
+        roll_possibilities,
+        bits_per_word,
+      }
+    })
+  }
+}
+
+/// Character classes that passphrases must contain, e.g. to comply with password policies. Classes
+/// are injected as follows, keeping the entropy of words intact:
+///
+/// - uppercase: if the formatted passphrase has no uppercase letters, its first lowercase letter is
+///   uppercased. This is deterministic, so it adds no entropy.
+/// - digit: at least one random digit is appended, as with [Passphraser::numbers].
+/// - symbol: a random symbol is inserted, as with [Passphraser::symbols], picked from
+///   [Passphraser::SYMBOLS_DEFAULT] unless other symbols are set.
+///
+/// Entropy of the added digit and symbol is accounted for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Policy {
+  /// Whether an uppercase letter is required.
+  pub uppercase: bool,
+  /// Whether a digit is required.
+  pub digit: bool,
+  /// Whether a symbol is required.
+  pub symbol: bool,
+}
+
+impl Policy {
+  /// Policy requiring an uppercase letter, a digit and a symbol.
+  pub const STRICT: Policy = Policy {
+    uppercase: true,
+    digit: true,
+    symbol: true,
+  };
+}
+
+/// Errors that can occur during checked passphrase generation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerateError {
+  /// Rolled index has no matching word in the wordlist.
+  MissingIndex(usize),
+  /// Wordlist has no words to pick from.
+  EmptyWordlist,
+  /// Number of words to generate is zero.
+  ZeroLength,
+  /// Passphrase has no wordlist to re-roll words from, see [Passphrase::reroll].
+  NoWordlist,
+  /// Unique words are requested, but the wordlist has fewer words to pick from.
+  TooFewWords {
+    /// Number of words to generate.
+    length: usize,
+    /// Number of unique words to pick from.
+    words: usize,
+  },
+  /// Distinct passphrases are requested, but only some of them were generated before running out
+  /// of re-rolls.
+  TooFewCandidates {
+    /// Number of passphrases to generate.
+    requested: usize,
+    /// Number of distinct passphrases generated.
+    generated: usize,
+  },
+  /// Given roll can't be made with the configured dice, e.g. has a 0 or too few numbers.
+  InvalidRoll(Vec<usize>),
+  /// Picked words kept being re-rolled until [Passphraser::REROLLS_MAX] re-rolls in a row, e.g.
+  /// because they were already picked or are banned.
+  RerollsExhausted,
+}
+
+impl fmt::Display for GenerateError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      | Self::MissingIndex(index) => write!(f, "no word found for index {index} in the wordlist"),
+      | Self::EmptyWordlist => write!(f, "no words left in the wordlist to pick from"),
+      | Self::ZeroLength => write!(f, "number of words to generate must be at least 1"),
+      | Self::NoWordlist => write!(f, "passphrase has no wordlist to re-roll words from"),
+      | Self::TooFewWords { length, words } => {
+        write!(
+          f,
+          "can't pick {length} unique words from {words} words in the wordlist"
+        )
+      },
+      | Self::TooFewCandidates {
+        requested,
+        generated,
+      } => {
+        write!(
+          f,
+          "generated only {generated} distinct passphrases out of {requested}"
+        )
+      },
+      | Self::InvalidRoll(roll) => {
+        let roll = roll
+          .iter()
+          .map(usize::to_string)
+          .collect::<Vec<_>>()
+          .join("-");
+
+        write!(f, "roll {roll} can't be made with the configured dice")
+      },
+      | Self::RerollsExhausted => {
+        write!(
+          f,
+          "gave up picking a word after {} re-rolls in a row",
+          Passphraser::REROLLS_MAX
+        )
+      },
+    }
+  }
+}
+
+impl Error for GenerateError {}
+
+/// Error returned when a wordlist doesn't contain every expected index exactly once.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WordlistError {
+  /// Expected indices that are missing from the wordlist.
+  pub missing: Vec<usize>,
+  /// Indices that occur in the wordlist more than once.
+  pub duplicates: Vec<usize>,
+}
+
+impl WordlistError {
+  /// How much indices of each kind to list in the error message.
+  const DISPLAY_LIMIT: usize = 5;
+
+  fn fmt_indices(f: &mut fmt::Formatter<'_>, kind: &str, indices: &[usize]) -> fmt::Result {
+    let listed = indices
+      .iter()
+      .take(Self::DISPLAY_LIMIT)
+      .map(usize::to_string)
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    let ellipsis = if indices.len() > Self::DISPLAY_LIMIT {
+      ", ..."
+    } else {
+      ""
+    };
+
+    write!(f, "{} {kind} ({listed}{ellipsis})", indices.len())
+  }
+}
+
+impl fmt::Display for WordlistError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("wordlist has ")?;
+
+    if !self.missing.is_empty() {
+      Self::fmt_indices(f, "missing indices", &self.missing)?;
+    }
+
+    if !self.missing.is_empty() && !self.duplicates.is_empty() {
+      f.write_str(" and ")?;
+    }
+
+    if !self.duplicates.is_empty() {
+      Self::fmt_indices(f, "duplicate indices", &self.duplicates)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl Error for WordlistError {}
+
+/// Entry of an indexed wordlist, i.e. a word along with the index it's rolled with, see
+/// [parse_entries].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WordlistEntry {
+  /// Index built from dice rolls, e.g. `11111`.
+  pub index: usize,
+  /// Word associated with the index.
+  pub word: String,
+}
+
+impl WordlistEntry {
+  /// Parses a single wordlist line in the form of `<index> <word>`, with the index and the word
+  /// separated by whitespace or a colon. Returns [None] for comments and lines that don't start
+  /// with a numeric index followed by a word. Anything after the word is ignored.
+  pub fn parse(line: &str) -> Option<Self> {
+    to_pair(to_components(line)).map(Self::from)
+  }
+}
+
+impl From<Pair> for WordlistEntry {
+  fn from((index, word): Pair) -> Self {
+    Self { index, word }
+  }
+}
+
+/// Statistics of a wordlist, see [wordlist_stats].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WordlistStats {
+  /// Number of words, including duplicate ones.
+  pub entries: usize,
+  /// Length of the shortest word in chars.
+  pub min_length: usize,
+  /// Length of the longest word in chars.
+  pub max_length: usize,
+  /// Average length of words in chars.
+  pub average_length: f32,
+  /// Number of words that repeat an earlier word.
+  pub duplicates: usize,
+  /// Entropy of a word picked uniformly from unique words, in bits.
+  pub bits_per_word: f32,
+}
+
+/// Timing of generating a batch of passphrases, see `Passphraser::generate_many_timed`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GenerationStats {
+  /// Number of generated passphrases.
+  pub count: usize,
+  /// Time taken to generate them.
+  pub duration: Duration,
+  /// Sum of entropy of all passphrases, in bits.
+  pub total_bits: f64,
+}
+
+impl GenerationStats {
+  /// Creates stats of given passphrases generated in the given time, e.g. when generating them in
+  /// a custom loop or with a custom RNG.
+  pub fn new(passphrases: &[Passphrase], duration: Duration) -> Self {
+    Self {
+      count: passphrases.len(),
+      duration,
+      total_bits: passphrases
+        .iter()
+        .map(|passphrase| passphrase.entropy.entropy as f64)
+        .sum(),
+    }
+  }
+
+  /// Returns how much entropy was generated per second. Returns infinity if no time was measured.
+  pub fn bits_per_second(&self) -> f64 {
+    self.total_bits / self.duration.as_secs_f64()
+  }
+
+  /// Returns how many passphrases were generated per second. Returns infinity if no time was
+  /// measured.
+  pub fn passphrases_per_second(&self) -> f64 {
+    self.count as f64 / self.duration.as_secs_f64()
+  }
+}
+
+/// Errors that can occur when parsing dice rolls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RollsError {
+  /// Group at the given position, counting from 1, has a wrong number of rolls.
+  Length {
+    /// Position of the group, counting from 1.
+    position: usize,
+    /// Group as given in the input.
+    group: String,
+    /// Number of rolls each group must have.
+    expected: usize,
+  },
+  /// Group at the given position, counting from 1, has a char that is not a number in the range of
+  /// dice faces.
+  OutOfRange {
+    /// Position of the group, counting from 1.
+    position: usize,
+    /// Group as given in the input.
+    group: String,
+    /// Number of faces of the first die that is out of range.
+    faces: usize,
+  },
+  /// Compact rolls have a char that is not a digit in the base of dice faces.
+  InvalidDigit {
+    /// Char that isn't a valid digit.
+    digit: char,
+    /// Number of faces of the die, i.e. the base of its digits.
+    faces: usize,
+  },
+}
+
+impl fmt::Display for RollsError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      | Self::Length {
+        position,
+        group,
+        expected,
+      } => {
+        write!(
+          f,
+          "roll {position} (`{group}`) must have exactly {expected} digits"
+        )
+      },
+      | Self::OutOfRange {
+        position,
+        group,
+        faces,
+      } => {
+        write!(
+          f,
+          "roll {position} (`{group}`) must have only digits from 1 to {faces}"
+        )
+      },
+      | Self::InvalidDigit { digit, faces } => {
+        write!(f, "`{digit}` is not a digit in base {faces}")
+      },
+    }
+  }
+}
+
+impl Error for RollsError {}
+
+/// Error returned when parsing a [Preset] from an unknown name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsePresetError {
+  /// Name that doesn't match any preset.
+  pub name: String,
+}
+
+impl fmt::Display for ParsePresetError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "unknown preset `{}`, expected one of: {}",
+      self.name,
+      Preset::NAMES.join(", ")
+    )
+  }
+}
+
+impl Error for ParsePresetError {}
+
+/// Unified error of the crate that errors of specific operations convert into, e.g. to handle them
+/// all with a single type and `?`. Each variant wraps the original error, which it's displayed as.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DicewareError {
+  /// Passphrase generation failed.
+  Generate(GenerateError),
+  /// Wordlist doesn't contain every expected index exactly once.
+  Wordlist(WordlistError),
+  /// Dice rolls are malformed.
+  Rolls(RollsError),
+  /// Preset name is unknown.
+  Preset(ParsePresetError),
+}
+
+impl fmt::Display for DicewareError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      | Self::Generate(err) => err.fmt(f),
+      | Self::Wordlist(err) => err.fmt(f),
+      | Self::Rolls(err) => err.fmt(f),
+      | Self::Preset(err) => err.fmt(f),
+    }
+  }
+}
+
+impl Error for DicewareError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      | Self::Generate(err) => err.source(),
+      | Self::Wordlist(err) => err.source(),
+      | Self::Rolls(err) => err.source(),
+      | Self::Preset(err) => err.source(),
+    }
+  }
+}
+
+impl From<GenerateError> for DicewareError {
+  fn from(err: GenerateError) -> Self {
+    Self::Generate(err)
+  }
+}
+
+impl From<WordlistError> for DicewareError {
+  fn from(err: WordlistError) -> Self {
+    Self::Wordlist(err)
+  }
+}
+
+impl From<RollsError> for DicewareError {
+  fn from(err: RollsError) -> Self {
+    Self::Rolls(err)
+  }
+}
+
+impl From<ParsePresetError> for DicewareError {
+  fn from(err: ParsePresetError) -> Self {
+    Self::Preset(err)
+  }
+}
+
+/// Contains information about entropy.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entropy {
+  /// How much unique words (possibilites) contains the wordlist.
+  pub possibilities: usize,
+  /// Calculated entropy of the passphrase.
+  pub entropy: f32,
+  /// Minimum entropy the passphrase was generated for, if set.
+  #[cfg_attr(
+    feature = "serde",
+    serde(default, skip_serializing_if = "Option::is_none")
+  )]
+  pub target: Option<f32>,
+}
+
+impl Entropy {
+  pub fn new(possibilities: usize, phrase_length: usize) -> Self {
+    Entropy {
+      possibilities,
+      entropy: calc_entropy(possibilities, phrase_length),
+      target: None,
+    }
+  }
+
+  /// Returns how much the achieved entropy exceeds the target one, or [None] if there's no target.
+  pub fn overshoot(&self) -> Option<f32> {
+    self.target.map(|target| self.entropy - target)
+  }
+
+  /// Returns entropy of a single word in bits, i.e. `log2(possibilities)`.
+  pub fn bits_per_word(&self) -> f32 {
+    calc_entropy(self.possibilities, 1)
+  }
+}
+
+/// Strength category of a passphrase, derived from its entropy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strength {
+  /// Less than 40 bits by default.
+  Weak,
+  /// At least 40, but less than 60 bits by default.
+  Fair,
+  /// At least 60, but less than 80 bits by default.
+  Strong,
+  /// At least 80 bits by default.
+  Excellent,
+}
+
+impl Strength {
+  /// Default minimum entropy in bits for [Strength::Fair], [Strength::Strong] and
+  /// [Strength::Excellent] respectively.
+  pub const THRESHOLDS_DEFAULT: [f32; 3] = [40.0, 60.0, 80.0];
+
+  /// Returns the strength for the given entropy using default thresholds.
+  pub fn from_entropy(bits: f32) -> Self {
+    Self::from_entropy_with(bits, Self::THRESHOLDS_DEFAULT)
+  }
+
+  /// Returns the strength for the given entropy using the given minimum entropy in bits for
+  /// [Strength::Fair], [Strength::Strong] and [Strength::Excellent] respectively.
+  pub fn from_entropy_with(bits: f32, thresholds: [f32; 3]) -> Self {
+    let [fair, strong, excellent] = thresholds;
+
+    match bits {
+      | bits if bits >= excellent => Self::Excellent,
+      | bits if bits >= strong => Self::Strong,
+      | bits if bits >= fair => Self::Fair,
+      | _ => Self::Weak,
+    }
+  }
+}
+
+impl fmt::Display for Strength {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let label = match self {
+      | Self::Weak => "weak",
+      | Self::Fair => "fair",
+      | Self::Strong => "strong",
+      | Self::Excellent => "excellent",
+    };
+
+    f.write_str(label)
+  }
+}
+
+/// Contains generated passphrase words, formatting preset and calculated entropy.
+#[derive(Debug)]
+pub struct Passphrase {
+  preset: Preset,
+  entropy: Entropy,
+  words: Vec<String>,
+  rolls: Vec<Vec<usize>>,
+  indices: Vec<usize>,
+  numbers: String,
+  symbol: Option<(usize, char)>,
+  casing: Vec<bool>,
+  capitalize: bool,
+  normalize_case: bool,
+  checksum: bool,
+  uppercase: bool,
+  delimiter: Option<String>,
+  group_size: usize,
+  group_delimiter: Option<String>,
+  delimiters: Vec<Option<String>>,
+  prefix: String,
+  suffix: String,
+  origin: Option<Origin>,
+}
+
+/// Builder a passphrase was generated with, kept to re-roll its words with [Passphrase::reroll].
+/// It's shared between passphrases generated together, e.g. with [Passphraser::generate_many_with].
+#[derive(Clone)]
+struct Origin(Arc<Passphraser>);
+
+impl fmt::Debug for Origin {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Passphraser { .. }")
+  }
+}
+
+impl Passphrase {
+  const DELIM_DEFAULT: &'static str = " ";
+  const DELIM_KEBABCASE: &'static str = "-";
+  const DELIM_PASCALCASE: &'static str = "";
+  const DELIM_SNAKECASE: &'static str = "_";
+  const DELIM_TITLECASE: &'static str = " ";
+
+  /// Creates a [Passphrase] from given words without rolling dice, e.g. to re-format a passphrase
+  /// chosen elsewhere. Entropy is calculated as if the words were picked from a wordlist of the
+  /// given size.
+  pub fn from_words(words: Vec<String>, wordlist_size: usize, preset: Preset) -> Self {
+    Passphrase {
+      preset,
+      entropy: Entropy::new(wordlist_size, words.len()),
+      words,
+      rolls: Vec::new(),
+      indices: Vec::new(),
+      numbers: String::new(),
+      symbol: None,
+      casing: Vec::new(),
+      capitalize: false,
+      normalize_case: false,
+      checksum: false,
+      uppercase: false,
+      delimiter: None,
+      group_size: 0,
+      group_delimiter: None,
+      delimiters: Vec::new(),
+      prefix: String::new(),
+      suffix: String::new(),
+      origin: None,
+    }
+  }
+
+  /// Returns generated passphrase words.
+  pub fn words(&self) -> &[String] {
+    &self.words
+  }
+
+  /// Returns dice rolls of words, in the same order. Rolls are empty if words were picked
+  /// uniformly, i.e. from a plain wordlist or with excluded words. The check word, if any, has no
+  /// roll.
+  pub fn rolls(&self) -> &[Vec<usize>] {
+    &self.rolls
+  }
+
+  /// Encodes dice rolls of words as a single string, e.g. `0000012345` for `11111 23456`, to note
+  /// them down compactly. Each roll of a die is written as a digit in the base of the number of its
+  /// faces, i.e. starting from 0. Returns an empty string if words weren't picked by rolling dice.
+  /// See `Passphraser::from_rolls_compact`.
+  pub fn rolls_compact(&self) -> String {
+    self
+      .rolls
+      .iter()
+      .flatten()
+      .filter_map(|&n| char::from_digit(n.saturating_sub(1) as u32, 36))
+      .collect()
+  }
+
+  /// Returns the check word appended with [Passphraser::checksum], if any. It's always the last
+  /// word.
+  pub fn check_word(&self) -> Option<&str> {
+    self
+      .checksum
+      .then(|| self.words.last().map(String::as_str))
+      .flatten()
+  }
+
+  /// Returns wordlist indices of words, built from their rolls, in the same order. Like rolls,
+  /// indices are empty if words were picked uniformly. See [Passphrase::rolls].
+  pub fn indices(&self) -> &[usize] {
+    &self.indices
+  }
+
+  /// Returns calculated passphrase [Entropy].
+  pub fn entropy(&self) -> &Entropy {
+    &self.entropy
+  }
+
+  /// Returns the [Strength] of the passphrase using default thresholds. See
+  /// [Strength::from_entropy].
+  pub fn strength(&self) -> Strength {
+    Strength::from_entropy(self.entropy.entropy)
+  }
+
+  /// Returns a heuristic memorability score from 0 to 1, higher meaning easier to recall. Each
+  /// word is scored as the average of two parts, and the score is the average over words:
+  ///
+  /// - length: 1 for words of up to 3 chars, falling linearly to 0 at 12 chars.
+  /// - pronounceability: 1 if consonants never follow each other, falling by 0.25 with every
+  ///   consonant in the longest run of them after the first one. Common words tend to have short
+  ///   consonant runs, so this stands in for word frequency, which isn't bundled.
+  ///
+  /// Non-letter chars count as consonants. The score depends only on words, so it's the same for
+  /// the same words. Returns 0 if there are no words.
+  pub fn memorability(&self) -> f32 {
+    if self.words.is_empty() {
+      return 0.0;
+    }
+
+    let total = self
+      .words
+      .iter()
+      .map(|word| word_memorability(word))
+      .sum::<f32>();
+
+    total / self.words.len() as f32
+  }
+
+  /// Returns the first letters of words in upper case, e.g. `CHBS` for `correct horse battery
+  /// staple`, to help memorizing the passphrase.
+  pub fn acronym(&self) -> String {
+    self
+      .words
+      .iter()
+      .filter_map(|word| word.chars().next())
+      .flat_map(char::to_uppercase)
+      .collect()
+  }
+
+  /// Spells the formatted passphrase using the NATO phonetic alphabet, e.g. `hotel echo dash one`
+  /// for `he-1`, to help reading it aloud. Uppercase letters are prefixed with `capital`, digits
+  /// are spelled as words, some symbols are named, and other chars are kept as is.
+  pub fn phonetic(&self) -> String {
+    self
+      .format()
+      .chars()
+      .map(to_phonetic)
+      .collect::<Vec<_>>()
+      .join(" ")
+  }
+
+  /// Appends words of another passphrase to this one, e.g. to compose a long secret from
+  /// independently generated segments. Entropy is summed, while its possibilities and formatting,
+  /// including the preset, are kept from this passphrase. Digits are appended, and the symbol of
+  /// the other passphrase is attached to its word if this one already has a symbol. Random
+  /// delimiters of both passphrases are kept, while they're joined with the delimiter of the
+  /// preset. The check word of this passphrase becomes an ordinary word. Rolls and indices are
+  /// kept only if every word of both passphrases has one.
+  pub fn concat(mut self, mut other: Passphrase) -> Passphrase {
+    let offset = self.words.len();
+    let rolled = |passphrase: &Passphrase| passphrase.rolls.len() == passphrase.words.len();
+    let keep_rolls = rolled(&self) && rolled(&other);
+
+    self.symbol = match (self.symbol, other.symbol) {
+      | (Some(symbol), Some((position, other_symbol))) => {
+        other.words[position].push(other_symbol);
+        Some(symbol)
+      },
+      | (None, Some((position, symbol))) => Some((offset + position, symbol)),
+      | (symbol, None) => symbol,
+    };
+
+    if !other.casing.is_empty() {
+      self.casing.resize(offset, false);
+      self.casing.extend(other.casing);
+    }
+
+    if !other.delimiters.is_empty() {
+      self.delimiters.resize(offset, None);
+      self.delimiters.extend(other.delimiters);
+    }
+
+    if keep_rolls {
+      self.rolls.extend(other.rolls);
+      self.indices.extend(other.indices);
+    } else {
+      self.rolls.clear();
+      self.indices.clear();
+    }
+
+    self.words.extend(other.words);
+    self.numbers.push_str(&other.numbers);
+    self.checksum = other.checksum;
+    self.entropy.entropy += other.entropy.entropy;
+    self
+  }
+
+  /// Re-rolls the word at the given position using the wordlist and configuration the passphrase
+  /// was generated with, e.g. to replace a single word one doesn't like. Everything else, including
+  /// the symbol and casing at that position, is kept, and so is entropy, since the new word is
+  /// picked the same way. The check word, if any, is recomputed. If the new word is picked without
+  /// rolls, e.g. with excluded words, rolls and indices are cleared, since they no longer match.
+  ///
+  /// Returns an error if the passphrase wasn't generated by [Passphraser], e.g. was created with
+  /// [Passphrase::from_words], or if the rolled index has no word.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the position is out of bounds or points to the check word.
+  pub fn reroll<R: Rng + ?Sized>(
+    &mut self,
+    position: usize,
+    rng: &mut R,
+  ) -> Result<(), GenerateError> {
+    let picked = self.picked_count();
+
+    assert!(
+      position < picked,
+      "position {position} is out of bounds for {picked} picked words"
+    );
+
+    let Origin(builder) = self.origin.clone().ok_or(GenerateError::NoWordlist)?;
+
+    let picks = if builder.unique_words {
+      let mut others = self.words[..picked].to_vec();
+      others.remove(position);
+
+      builder.pick_unique(rng, 1, &others)
+    } else {
+      builder.pick(rng, 1)
+    };
+
+    let (word, roll) = picks
+      .into_iter()
+      .next()
+      .expect("one word is always picked")?;
+
+    match roll {
+      | Some(roll) if position < self.rolls.len() => {
+        self.indices[position] = builder.index_of(&roll);
+        self.rolls[position] = roll;
+      },
+      | Some(_) => {},
+      | None => {
+        self.rolls.clear();
+        self.indices.clear();
+      },
+    }
+
+    self.words[position] = word;
+
+    if self.checksum {
+      if let Some(check_word) = builder.check_word(&self.words[..picked]) {
+        self.words[picked] = check_word;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Shuffles words using the given random number generator. The check word, if any, stays last.
+  /// Since words are picked independently, shuffling doesn't increase entropy.
+  pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+    let mut order = (0..self.picked_count()).collect::<Vec<_>>();
+    order.shuffle(rng);
+
+    self.reorder(&order);
+  }
+
+  /// Reverses the order of words. The check word, if any, stays last.
+  pub fn reverse(&mut self) {
+    let order = (0..self.picked_count()).rev().collect::<Vec<_>>();
+
+    self.reorder(&order);
+  }
+
+  /// Returns the number of words, excluding the check word.
+  fn picked_count(&self) -> usize {
+    self.words.len() - usize::from(self.checksum)
+  }
+
+  /// Reorders picked words according to the given order of their positions, moving the inserted
+  /// symbol and random casing along with them.
+  fn reorder(&mut self, order: &[usize]) {
+    let words = order
+      .iter()
+      .map(|&i| self.words[i].clone())
+      .collect::<Vec<_>>();
+    self.words.splice(..order.len(), words);
+
+    if !self.casing.is_empty() {
+      self.casing = order.iter().map(|&i| self.casing[i]).collect();
+    }
+
+    if !self.rolls.is_empty() {
+      self.rolls = order.iter().map(|&i| self.rolls[i].clone()).collect();
+      self.indices = order.iter().map(|&i| self.indices[i]).collect();
+    }
+
+    if let Some((position, symbol)) = self.symbol {
+      let position = order
+        .iter()
+        .position(|&i| i == position)
+        .unwrap_or(position);
+      self.symbol = Some((position, symbol));
+    }
+  }
+
+  /// Formats passphrase using the passphrase's preset.
+  pub fn format(&self) -> String {
+    self.format_with(&self.preset)
+  }
+
+  /// Formats passphrase using the given preset, capitalizing words if requested, inserting a random
+  /// symbol and appending random digits if there are any.
+  pub fn format_with(&self, preset: &Preset) -> String {
+    let formatted = self.decorate(|words| {
+      match preset {
+        | Preset::Template(template) => {
+          template.render(words, &self.numbers, |word| self.capitalized(word))
+        },
+        | _ => self.format_words(words, preset) + &self.numbers,
+      }
+    });
+
+    format!("{}{formatted}{}", self.prefix, self.suffix)
+  }
+
+  /// Formats passphrase using the given options instead of a preset, e.g. for one-off formatting.
+  /// The delimiter, prefix and suffix of the options are used as is, while capitalization, the
+  /// symbol, digits and grouping of the passphrase are still applied.
+  pub fn format_opts(&self, options: &FormatOptions) -> String {
+    let formatted = self.decorate(|words| self.format_options(words, options) + &self.numbers);
+
+    format!("{}{formatted}{}", options.prefix, options.suffix)
+  }
+
+  /// Formats passphrase words with the given function, after capitalizing words if requested and
+  /// inserting a random symbol, and then uppercases a letter if the policy requires it.
+  fn decorate(&self, format: impl FnOnce(&[String]) -> String) -> String {
+    let mut words = if self.capitalize {
+      self
+        .words
+        .iter()
+        .map(|word| self.capitalized(word))
+        .collect()
+    } else {
+      self.words.clone()
+    };
+
+    if let Some((position, symbol)) = self.symbol {
+      words[position].push(symbol);
+    }
+
+    let formatted = format(&words);
+
+    if self.uppercase {
+      to_uppercased_once(&formatted)
+    } else {
+      formatted
+    }
+  }
+
+  /// Formats passphrase using every preset of [Preset::NAMES], returning `(name, formatted)` pairs
+  /// in the same order, e.g. to show the passphrase in each style.
+  pub fn format_all(&self) -> Vec<(String, String)> {
+    Preset::NAMES
+      .iter()
+      .filter_map(|&name| Preset::try_from_name(name).map(|preset| (name, preset)))
+      .map(|(name, preset)| (name.to_string(), self.format_with(&preset)))
+      .collect()
+  }
+
+  /// Formats given words using the given preset. Since [Preset::RandomCase] relies on casing drawn
+  /// during generation, it leaves words as is if the passphrase was generated with another preset.
+  /// The delimiter, if set, replaces the one of the preset, keeping the rest of its styling.
+  fn format_words(&self, words: &[String], preset: &Preset) -> String {
+    if let Some(mut options) = preset.format_options() {
+      if let Some(delimiter) = &self.delimiter {
+        options.delimiter = delimiter.clone();
+      }
+
+      return self.format_options(words, &options);
+    }
+
+    let delimiter = self.delimiter.as_deref().unwrap_or(Self::DELIM_DEFAULT);
+
+    match &preset {
+      | Preset::RandomCase => {
+        let mut casing = self.casing.iter().chain(std::iter::repeat(&false));
+
+        let words = words
+          .iter()
+          .map(|word| {
+            if *casing.next().unwrap() {
+              self.capitalized(word)
+            } else {
+              word.clone()
+            }
+          })
+          .collect::<Vec<_>>();
+
+        self.join(&words, delimiter)
+      },
+      | Preset::CapitalizePositions(positions) => {
+        let words = words
+          .iter()
+          .enumerate()
+          .map(|(position, word)| {
+            if positions.contains(&position) {
+              self.capitalized(word)
+            } else {
+              word.clone()
+            }
+          })
+          .collect::<Vec<_>>();
+
+        self.join(&words, delimiter)
+      },
+      | Preset::Delimiters(_) if self.delimiter.is_some() => self.join(words, delimiter),
+      | Preset::Delimiters(delimiters) => {
+        let mut delimiters = delimiters.iter().cycle();
+        let mut words = words.iter();
+        let first = words.next().cloned().unwrap_or_default();
+
+        words.fold(first, |acc, word| acc + delimiters.next().unwrap() + word)
+      },
+      | Preset::Template(template) => template.render(words, "", |word| self.capitalized(word)),
+      | _ => self.join(words, delimiter),
+    }
+  }
+
+  /// Changes case of given words and joins them as specified by the options.
+  fn format_options(&self, words: &[String], options: &FormatOptions) -> String {
+    let words = words
+      .iter()
+      .enumerate()
+      .map(|(position, word)| {
+        let word = match options.case {
+          | Case::Keep => word.clone(),
+          | Case::Lower => word.to_lowercase(),
+          | Case::Upper => word.to_uppercase(),
+          | Case::Camel if position == 0 => word.to_lowercase(),
+          | Case::Camel => self.capitalized(word),
+        };
+
+        if options.capitalize {
+          self.capitalized(&word)
+        } else {
+          word
+        }
+      })
+      .collect::<Vec<_>>();
+
+    self.join(&words, &options.delimiter)
+  }
+
+  /// Joins words using specified delimiter, or using delimiters drawn during generation if there
+  /// are any, and using the group delimiter between groups of words if grouping is enabled.
+  fn join(&self, words: &[String], delimiter: &str) -> String {
+    if self.group_size == 0 && self.delimiters.is_empty() {
+      return words.join(delimiter);
+    }
+
+    let group_delimiter = self
+      .group_delimiter
+      .as_deref()
+      .unwrap_or(Self::DELIM_DEFAULT);
+
+    let mut joined = String::new();
+
+    for (position, word) in words.iter().enumerate() {
+      if position > 0 && self.group_size > 0 && position % self.group_size == 0 {
+        joined.push_str(group_delimiter);
+      } else if position > 0 {
+        let drawn = self.delimiters.get(position - 1).and_then(Option::as_deref);
+        joined.push_str(drawn.unwrap_or(delimiter));
+      }
+
+      joined.push_str(word);
+    }
+
+    joined
+  }
+
+  /// Capitalizes given word, also lowercasing the rest of it if case normalization is enabled.
+  fn capitalized(&self, word: &str) -> String {
+    if self.normalize_case {
+      to_title_cased(word)
+    } else {
+      to_capitalized(word)
+    }
+  }
+}
+
+impl fmt::Display for Passphrase {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.format())
+  }
+}
+
+/// Serializes the formatted passphrase along with its words, preset and entropy.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Passphrase {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+
+    let mut state = serializer.serialize_struct("Passphrase", 4)?;
+    state.serialize_field("passphrase", &self.format())?;
+    state.serialize_field("words", &self.words)?;
+    state.serialize_field("preset", &self.preset)?;
+    state.serialize_field("entropy", &self.entropy)?;
+    state.end()
+  }
+}
+
+/// Serializable view of a [Passphrase] that also has a `formats` map of every preset name to the
+/// passphrase formatted with it, see [Passphrase::with_formats].
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug)]
+pub struct WithFormats<'a>(&'a Passphrase);
+
+#[cfg(feature = "serde")]
+impl Passphrase {
+  /// Returns a view of the passphrase that serializes like the passphrase itself, plus a `formats`
+  /// map as returned by [Passphrase::format_all], e.g. to let a UI switch styles without
+  /// generating a new passphrase.
+  pub fn with_formats(&self) -> WithFormats<'_> {
+    WithFormats(self)
+  }
+}
+
+/// Serializes the formatted passphrase along with its words, preset, entropy and formats.
+#[cfg(feature = "serde")]
+impl serde::Serialize for WithFormats<'_> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+
+    /// Serializes `(name, formatted)` pairs as a map, keeping their order.
+    struct Formats(Vec<(String, String)>);
+
+    impl serde::Serialize for Formats {
+      fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(name, formatted)| (name, formatted)))
+      }
+    }
+
+    let passphrase = self.0;
+    let mut state = serializer.serialize_struct("Passphrase", 5)?;
+    state.serialize_field("passphrase", &passphrase.format())?;
+    state.serialize_field("words", &passphrase.words)?;
+    state.serialize_field("preset", &passphrase.preset)?;
+    state.serialize_field("entropy", &passphrase.entropy)?;
+    state.serialize_field("formats", &Formats(passphrase.format_all()))?;
+    state.end()
+  }
+}
+
+/// Rolls a dice, producing a vector of numbers for each run. Numbers are in the `start..end` range,
+/// i.e. `end` is exclusive, so a regular six-sided die is rolled with `start = 1` and `end = 7`.
+#[cfg(feature = "thread-rng")]
+pub fn roll_dice(runs: usize, rolls: usize, start: usize, end: usize) -> Vec<Vec<usize>> {
+  roll_dice_with(&mut rand::thread_rng(), runs, rolls, start, end)
+}
+
+/// Same as `roll_dice`, but uses the given random number generator.
+pub fn roll_dice_with<R: Rng + ?Sized>(
+  rng: &mut R,
+  runs: usize,
+  rolls: usize,
+  start: usize,
+  end: usize,
+) -> Vec<Vec<usize>> {
+  (1..=runs)
+    .map(|_| (1..=rolls).map(|_| rng.gen_range(start..end)).collect())
+    .collect()
+}
+
+/// Generates a string of random decimal digits of the given length.
+pub(crate) fn random_digits<R: Rng + ?Sized>(rng: &mut R, length: usize) -> String {
+  (0..length)
+    .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+    .collect()
+}
+
+/// Given a wordlist and dice rolls, generates a Diceware passphrase as a [Vec] of words. Rolls can
+/// be made with physical dice and parsed with [parse_rolls]. Rolls without a matching word in the
+/// wordlist are skipped.
+pub fn passphrase(lines: &[String], dice_rolls: Vec<Vec<usize>>) -> Vec<String> {
+  resolve(&to_entries(lines), dice_rolls)
+}
+
+/// Given parsed wordlist entries and dice rolls, picks words for rolled indices, skipping missing
+/// ones.
+pub(crate) fn resolve(entries: &Entries, dice_rolls: Vec<Vec<usize>>) -> Vec<String> {
+  dice_rolls
+    .into_iter()
+    .filter_map(|roll| entries.get(&to_index(roll)).cloned())
+    .collect()
+}
+
+/// Parses groups of dice rolls, checking that every group has exactly `dice_count` digits, each in
+/// the `1..=dice_faces` range. Groups can be separated by whitespace, including newlines, commas or
+/// semicolons, and digits within groups by dashes or dots, so `11111 23456`,
+/// `1-1-1-1-1,2-3-4-5-6` and `1.1.1.1.1;2.3.4.5.6` are all the same. Errors report the position of
+/// the malformed group, counting from 1.
+pub fn parse_rolls(
+  input: &str,
+  dice_count: usize,
+  dice_faces: usize,
+) -> Result<Vec<Vec<usize>>, RollsError> {
+  parse_groups(input, dice_count, |_| dice_faces)
+}
+
+/// Same as [parse_rolls], but checks each roll against the number of faces of the die at its
+/// position within the group, e.g. of the die selecting a merged wordlist.
+pub(crate) fn parse_groups(
+  input: &str,
+  dice_count: usize,
+  faces_of: impl Fn(usize) -> usize,
+) -> Result<Vec<Vec<usize>>, RollsError> {
+  input
+    .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+    .filter(|group| !group.is_empty())
+    .enumerate()
+    .map(|(index, group)| {
+      let position = index + 1;
+      let rolls = group
+        .chars()
+        .filter(|&c| c != '-' && c != '.')
+        .enumerate()
+        .map(|(die, c)| {
+          c.to_digit(10)
+            .map(|digit| digit as usize)
+            .filter(|digit| (1..=faces_of(die)).contains(digit))
+            .ok_or(die)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|die| {
+          RollsError::OutOfRange {
+            position,
+            group: group.to_string(),
+            faces: faces_of(die),
+          }
+        })?;
+
+      if rolls.len() != dice_count {
+        return Err(RollsError::Length {
+          position,
+          group: group.to_string(),
+          expected: dice_count,
+        });
+      }
+
+      Ok(rolls)
+    })
+    .collect()
+}
+
+/// Finds words that occur in a wordlist more than once, returning each such word along with all of
+/// its indices, sorted by word. Duplicate words of indexed wordlists make some words more likely to
+/// be rolled, so the real entropy is lower than the calculated one. Words are taken from
+/// `<index> <word>` pairs, or from whole lines if the wordlist is plain, in which case positions of
+/// words counted from 1 are returned instead of indices.
+pub fn duplicate_words(lines: &[String]) -> Vec<(String, Vec<usize>)> {
+  let mut indices = lines.iter().fold(HashMap::new(), |mut acc, line| {
+    if let Some((index, word)) = to_pair(to_components(line)) {
+      acc.entry(word).or_insert_with(Vec::new).push(index);
+    }
+
+    acc
+  });
+
+  if indices.is_empty() {
+    let words = lines
+      .iter()
+      .map(|line| line.trim())
+      .filter(|word| !word.is_empty() && !is_comment(word));
+
+    for (position, word) in words.enumerate() {
+      indices
+        .entry(word.to_string())
+        .or_insert_with(Vec::new)
+        .push(position + 1);
+    }
+  }
+
+  let mut duplicates = indices
+    .into_iter()
+    .filter(|(_, indices)| indices.len() > 1)
+    .collect::<Vec<_>>();
+
+  duplicates.sort_unstable();
+  duplicates
+}
+
+/// Calculates statistics of a wordlist, e.g. to check whether a custom wordlist is suitable. Words
+/// are taken from `<index> <word>` pairs, or from whole lines if the wordlist is plain.
+pub fn wordlist_stats(lines: &[String]) -> WordlistStats {
+  let mut words = lines
+    .iter()
+    .filter_map(|line| to_pair(to_components(line)).map(|(_, word)| word))
+    .collect::<Vec<_>>();
+
+  if words.is_empty() {
+    words = lines
+      .iter()
+      .map(|line| line.trim())
+      .filter(|word| !word.is_empty() && !is_comment(word))
+      .map(str::to_string)
+      .collect();
+  }
+
+  if words.is_empty() {
+    return WordlistStats::default();
+  }
+
+  let lengths = words.iter().map(|word| word.chars().count());
+  let unique = words.iter().collect::<HashSet<_>>().len();
+
+  WordlistStats {
+    entries: words.len(),
+    min_length: lengths.clone().min().unwrap_or(0),
+    max_length: lengths.clone().max().unwrap_or(0),
+    average_length: lengths.sum::<usize>() as f32 / words.len() as f32,
+    duplicates: words.len() - unique,
+    bits_per_word: calc_entropy(unique, 1),
+  }
+}
+
+/// Checks that a wordlist contains every index that can be rolled with five six-sided dice exactly
+/// once. Use [Passphraser::validate] to check against a different dice configuration.
+pub fn validate_wordlist(lines: &[String]) -> Result<(), WordlistError> {
+  validate_indices(
+    lines,
+    to_indices(
+      Passphraser::DICE_COUNT_DEFAULT,
+      Passphraser::DICE_FACES_DEFAULT,
+    ),
+  )
+}
+
+/// Checks that a wordlist contains every expected index exactly once.
+pub(crate) fn validate_indices(
+  lines: &[String],
+  expected: Vec<usize>,
+) -> Result<(), WordlistError> {
+  let counts = lines.iter().fold(HashMap::new(), |mut counts, line| {
+    if let Some((index, _)) = to_pair(to_components(line)) {
+      *counts.entry(index).or_insert(0) += 1;
+    }
+
+    counts
+  });
+
+  let missing = expected
+    .into_iter()
+    .filter(|index| !counts.contains_key(index))
+    .collect::<Vec<_>>();
+
+  let mut duplicates = counts
+    .into_iter()
+    .filter_map(|(index, count)| (count > 1).then_some(index))
+    .collect::<Vec<_>>();
+
+  duplicates.sort_unstable();
+
+  if missing.is_empty() && duplicates.is_empty() {
+    Ok(())
+  } else {
+    Err(WordlistError {
+      missing,
+      duplicates,
+    })
+  }
+}
+
+/// Concatenates several wordlists, e.g. themed sublists read from separate files, into one. Lines
+/// of indexed wordlists are kept as is, so an index occurring in more than one of them is an error
+/// rather than being re-indexed, since that would change which rolls pick which words. Indices
+/// duplicated within a single wordlist are left to [Passphraser::validate].
+pub fn concat_wordlists(lists: &[Vec<String>]) -> Result<Vec<String>, WordlistError> {
+  let mut owners = HashMap::new();
+  let mut duplicates = Vec::new();
+
+  for (list, lines) in lists.iter().enumerate() {
+    for (index, _) in lines.iter().filter_map(|line| to_pair(to_components(line))) {
+      if *owners.entry(index).or_insert(list) != list && !duplicates.contains(&index) {
+        duplicates.push(index);
+      }
+    }
+  }
+
+  if duplicates.is_empty() {
+    Ok(lists.concat())
+  } else {
+    duplicates.sort_unstable();
+
+    Err(WordlistError {
+      missing: Vec::new(),
+      duplicates,
+    })
+  }
+}
+
+/// Parses a wordlist from a text buffer, e.g. fetched over network or read from a file, and returns
+/// a vector of lines, which can then be passed to [Passphraser::wordlist]. Lines are trimmed, so
+/// carriage returns of CRLF line endings and surrounding whitespace don't end up in words. Blank
+/// lines and comments, i.e. lines starting with `#`, are skipped.
+pub fn parse_wordlist(text: &str) -> Vec<String> {
+  text
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !is_comment(line))
+    .map(str::to_string)
+    .collect()
+}
+
+/// Parses entries of an indexed wordlist from a text buffer, in the order they appear in, e.g. to
+/// build a custom lookup. Lines are parsed as with [WordlistEntry::parse], and lines that can't be
+/// parsed, including words of plain wordlists, are skipped. Duplicate indices are kept as is.
+pub fn parse_entries(text: &str) -> Vec<WordlistEntry> {
+  text.lines().filter_map(WordlistEntry::parse).collect()
+}
+
+/// Reads a wordlist from the file at the given path and returns a vector of lines. With the `gzip`
+/// feature enabled, files with the `.gz` extension are decompressed first.
+pub fn read_wordlist(path: impl AsRef<Path>) -> io::Result<Vec<String>> {
+  let path = path.as_ref();
+
+  #[cfg(feature = "gzip")]
+  if path.extension().is_some_and(|extension| extension == "gz") {
+    let file = std::fs::File::open(path)?;
+    let text = io::read_to_string(flate2::read::GzDecoder::new(file))?;
+
+    return Ok(parse_wordlist(&text));
+  }
+
+  Ok(parse_wordlist(&std::fs::read_to_string(path)?))
+}
+
+/// Reads a built-in EFF long wordlist and returns a vector of lines.
+pub fn builtin_wordlist() -> Vec<String> {
+  builtin_wordlist_ref().to_vec()
+}
+
+/// Returns the SHA-256 hash of the embedded EFF long wordlist text as a lowercase hex string, e.g.
+/// to confirm which revision of the list is built in.
+pub fn builtin_wordlist_hash() -> String {
+  use sha2::{Digest, Sha256};
+
+  Sha256::digest(EFF_WORDLIST.as_bytes())
+    .iter()
+    .map(|byte| format!("{byte:02x}"))
+    .collect()
+}
+
+/// Returns lines of the built-in EFF long wordlist without allocating them on every call, since the
+/// embedded text is split only once.
+pub fn builtin_wordlist_ref() -> &'static [String] {
+  builtin_lines()
+}
+
+/// Counts lines of a text the same way as [str::lines] does, but in const context.
+const fn count_lines(text: &str) -> usize {
+  let bytes = text.as_bytes();
+  let mut count = 0;
+  let mut i = 0;
+
+  while i < bytes.len() {
+    if bytes[i] == b'\n' {
+      count += 1;
+    }
+
+    i += 1;
+  }
+
+  if !bytes.is_empty() && bytes[bytes.len() - 1] != b'\n' {
+    count += 1;
+  }
+
+  count
+}
+
+/// Returns shared lines of the built-in EFF long wordlist, splitting it only once.
+pub(crate) fn builtin_lines() -> &'static Arc<[String]> {
+  EFF_LINES.get_or_init(|| parse_wordlist(EFF_WORDLIST).into())
+}
+
+/// Returns the built-in EFF long wordlist parsed into shared [Entries], parsing it only once.
+pub(crate) fn builtin_entries() -> &'static Arc<Entries> {
+  EFF_ENTRIES.get_or_init(|| Arc::new(to_entries(builtin_lines())))
+}
+
+/// Given a length (the number of possibilities, e.g. for the EFF long list it is 7776
+/// possibilities) of a wordlist and phrase length in words, calculates entropy of the phrase.
+pub fn calc_entropy(possibilities: usize, phrase_length: usize) -> f32 {
+  (possibilities as f32).log2() * (phrase_length as f32)
+}
+
+/// Same as [calc_entropy], but for words drawn without replacement, i.e. with no word repeated, so
+/// each next word is picked out of one possibility less. Words beyond the number of possibilities
+/// add no entropy.
+pub fn calc_entropy_no_replacement(possibilities: usize, phrase_length: usize) -> f32 {
+  (0..phrase_length.min(possibilities))
+    .map(|i| calc_entropy(possibilities - i, 1))
+    .sum()
+}
+
+/// Returns weights of words inversely proportional to their length in chars, see
+/// [Passphraser::prefer_short].
+pub(crate) fn short_weights<'a>(words: &'a [&str]) -> impl Iterator<Item = f64> + 'a {
+  words
+    .iter()
+    .map(|word| 1.0 / word.chars().count().max(1) as f64)
+}
+
+/// Parses wordlist lines into [Entries]. Unparseable lines are skipped, and if an index occurs more
+/// than once, the first word wins.
+pub(crate) fn to_entries(lines: &[String]) -> Entries {
+  lines.iter().fold(Entries::new(), |mut entries, line| {
+    if let Some((index, word)) = to_pair(to_components(line)) {
+      entries.entry(index).or_insert(word);
+    }
+
+    entries
+  })
+}
+
+/// Estimates the average time needed to guess a passphrase with given entropy at the given rate of
+/// guesses per second, i.e. the time to search half of the keyspace. Saturates at [Duration::MAX].
+pub fn crack_time(entropy_bits: f32, guesses_per_second: f64) -> Duration {
+  let guesses = 2f64.powf(entropy_bits as f64 - 1.0);
+
+  Duration::try_from_secs_f64(guesses / guesses_per_second).unwrap_or(Duration::MAX)
+}
+
+/// Formats a duration in the largest fitting unit, from seconds to centuries, e.g. `3 days`.
+pub fn format_duration(duration: Duration) -> String {
+  const MINUTE: f64 = 60.0;
+  const HOUR: f64 = MINUTE * 60.0;
+  const DAY: f64 = HOUR * 24.0;
+  const YEAR: f64 = DAY * 365.25;
+  const CENTURY: f64 = YEAR * 100.0;
+
+  let seconds = duration.as_secs_f64();
+
+  if seconds < 1.0 {
+    return "less than a second".to_string();
+  }
+
+  let (amount, unit) = [
+    (CENTURY, "century", "centuries"),
+    (YEAR, "year", "years"),
+    (DAY, "day", "days"),
+    (HOUR, "hour", "hours"),
+    (MINUTE, "minute", "minutes"),
+  ]
+  .into_iter()
+  .find(|(size, ..)| seconds >= *size)
+  .map_or((seconds.floor(), "seconds"), |(size, singular, plural)| {
+    let amount = (seconds / size).floor();
+    (amount, if amount == 1.0 { singular } else { plural })
+  });
+
+  if amount == 1.0 && unit == "seconds" {
+    "1 second".to_string()
+  } else {
+    format!("{amount:.0} {unit}")
+  }
+}
+
+/// Splits a given line into a vector of components. Components can be separated by any ASCII
+/// whitespace (e.g. spaces or tabs) or colons, so both `11111 abacus` and `11111:abacus` work.
+/// Comments have no components.
+pub(crate) fn to_components(line: &str) -> Vec<&str> {
+  if is_comment(line) {
+    return Vec::new();
+  }
+
+  line
+    .split(|c: char| c.is_ascii_whitespace() || c == ':')
+    .filter(|component| !component.is_empty())
+    .collect()
+}
+
+/// Checks whether a given wordlist line is a comment, i.e. starts with `#`.
+pub(crate) fn is_comment(line: &str) -> bool {
+  line.trim_start().starts_with('#')
+}
+
+/// Unpacks a given vector of line components in the form of `[index, word]` to a [Pair] struct.
+pub(crate) fn to_pair(components: Vec<&str>) -> Option<Pair> {
+  let mut components = components.iter();
+
+  if let (Some(index), Some(word)) = (components.next(), components.next()) {
+    match index.parse::<usize>() {
+      | Ok(index) => Some((index, word.to_string())),
+      | Err(_) => None,
+    }
+  } else {
+    None
+  }
+}
+
+/// Reduces a vector of rolled numbers to a single number which then will be used as an index in a
+/// Diceware wordlist.
+pub(crate) fn to_index(ns: Vec<usize>) -> usize {
+  IndexScheme::Concatenated.index(&ns, 10)
+}
+
+/// Returns all indices that can be rolled with given dice, in ascending order.
+pub(crate) fn to_indices(dice_count: usize, dice_faces: usize) -> Vec<usize> {
+  IndexScheme::Concatenated.indices(dice_count, dice_faces)
+}
+
+/// Capitalizes the first char of given string.
+pub(crate) fn to_capitalized(s: &str) -> String {
+  let mut chars = s.chars();
+
+  match chars.next() {
+    | None => String::new(),
+    | Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+  }
+}
+
+/// Uppercases the first lowercase char of given string, unless it already has uppercase chars.
+pub(crate) fn to_uppercased_once(s: &str) -> String {
+  if s.chars().any(char::is_uppercase) {
+    return s.to_string();
+  }
+
+  match s.char_indices().find(|(_, c)| c.is_lowercase()) {
+    | Some((index, c)) => {
+      let rest = &s[index + c.len_utf8()..];
+
+      s[..index].to_string() + &c.to_uppercase().collect::<String>() + rest
+    },
+    | None => s.to_string(),
+  }
+}
+
+/// Spells a single char using the NATO phonetic alphabet. See [Passphrase::phonetic].
+pub(crate) fn to_phonetic(c: char) -> String {
+  const LETTERS: [&str; 26] = [
+    "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "x-ray", "yankee", "zulu",
+  ];
+  const DIGITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+  ];
+
+  match c {
+    | 'a'..='z' => LETTERS[c as usize - 'a' as usize].to_string(),
+    | 'A'..='Z' => format!("capital {}", LETTERS[c as usize - 'A' as usize]),
+    | '0'..='9' => DIGITS[c as usize - '0' as usize].to_string(),
+    | ' ' => "space".to_string(),
+    | '-' => "dash".to_string(),
+    | '_' => "underscore".to_string(),
+    | '.' => "dot".to_string(),
+    | _ => c.to_string(),
+  }
+}
+
+/// Scores memorability of a single word. See [Passphrase::memorability].
+pub(crate) fn word_memorability(word: &str) -> f32 {
+  let length = word.chars().count() as f32;
+  let length = ((12.0 - length) / 9.0).clamp(0.0, 1.0);
+
+  let (_, run) = word
+    .chars()
+    .fold((0usize, 0usize), |(current, longest), c| {
+      if "aeiouy".contains(c.to_ascii_lowercase()) {
+        (0, longest)
+      } else {
+        (current + 1, longest.max(current + 1))
+      }
+    });
+
+  let pronounceability = (1.0 - run.saturating_sub(1) as f32 * 0.25).max(0.0f32);
+
+  (length + pronounceability) / 2.0
+}
+
+/// Capitalizes the first char of given string and lowercases the rest of it.
+pub(crate) fn to_title_cased(s: &str) -> String {
+  let mut chars = s.chars();
+
+  match chars.next() {
+    | None => String::new(),
+    | Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn passphrase_of(words: &[&str]) -> Passphrase {
+    Passphrase::from_words(
+      words.iter().map(ToString::to_string).collect(),
+      7776,
+      Preset::Default,
+    )
+  }
+
+  #[test]
+  #[should_panic]
+  #[cfg(feature = "thread-rng")]
+  fn test_roll_dice() {
+    roll_dice(6, 5, 0, 0);
+    roll_dice(6, 0, 0, 0);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_roll_dice_range() {
+    let rolls = roll_dice(1000, 5, 1, 7);
+
+    assert!(rolls.iter().flatten().all(|n| (1..=6).contains(n)));
+    assert!(rolls.iter().flatten().any(|&n| n == 6));
+
+    let has_six = rolls
+      .into_iter()
+      .map(to_index)
+      .any(|index| index.to_string().contains('6'));
+
+    assert!(has_six);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_dice_count() {
+    let wordlist = to_indices(4, 6)
+      .into_iter()
+      .map(|index| format!("{index} word{index}"))
+      .collect::<Vec<_>>();
+
+    let passphrase = Passphraser::new(8)
+      .wordlist(&wordlist)
+      .dice_count(4)
+      .generate();
+
+    assert_eq!(passphrase.words().len(), 8);
+    assert_eq!(passphrase.entropy().possibilities, 1296);
+    assert!(passphrase
+      .words()
+      .iter()
+      .all(|word| word.starts_with("word")));
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_builtin() {
+    let passphrase = Passphraser::new(6)
+      .dice_count(4)
+      .builtin(BuiltinWordlist::EffLong)
+      .generate_checked()
+      .unwrap();
+
+    assert_eq!(passphrase.words().len(), 6);
+    assert_eq!(passphrase.entropy().possibilities, 7776);
+
+    let wordlist = BuiltinWordlist::Bip39English.lines();
+    let passphrase = Passphraser::new(12)
+      .builtin(BuiltinWordlist::Bip39English)
+      .generate_checked()
+      .unwrap();
+
+    assert_eq!(wordlist.len(), 2048);
+    assert_eq!(passphrase.words().len(), 12);
+    assert!(passphrase
+      .words()
+      .iter()
+      .all(|word| wordlist.contains(word)));
+    assert_eq!(passphrase.entropy().possibilities, 2048);
+    assert_eq!(passphrase.entropy().entropy, 132.0);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_reset() {
+    let wordlist = vec!["11 foo".to_string(), "12 bar".to_string()];
+
+    let mut builder = Passphraser::new(10);
+    builder
+      .wordlist(&wordlist)
+      .dice_count(2)
+      .preset(Preset::KebabCase)
+      .delimiter(".")
+      .numbers(3)
+      .checksum(true);
+
+    builder.reset_preset();
+
+    assert!(matches!(builder.preset, Preset::Default));
+    assert_eq!(builder.delimiter, None);
+    assert_eq!(builder.numbers, 3);
+
+    builder.reset_wordlist();
+
+    assert_eq!(builder.wordlist.len(), BUILTIN_WORDLIST_SIZE);
+    assert_eq!(builder.dice_count, Passphraser::DICE_COUNT_DEFAULT);
+
+    let passphrase = builder.reset().generate();
+
+    assert_eq!(passphrase.words().len(), Passphraser::LENGTH_DEFAULT);
+    assert_eq!(passphrase.entropy().possibilities, 7776);
+    assert_eq!(passphrase.check_word(), None);
+    assert_eq!(passphrase.format().split(' ').count(), 6);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_for_entropy() {
+    assert_eq!(Passphraser::for_entropy(100.0, 7776), 8);
+    assert_eq!(Passphraser::for_entropy(calc_entropy(7776, 6), 7776), 6);
+    assert_eq!(Passphraser::for_entropy(77.6, 7776), 7);
+    assert_eq!(Passphraser::for_entropy(0.0, 7776), 0);
+    assert_eq!(Passphraser::for_entropy(10.0, 1), 0);
+    assert_eq!(Passphraser::for_entropy(f32::NAN, 7776), 0);
+
+    for bits in [f32::INFINITY, 1e12] {
+      assert_eq!(
+        Passphraser::for_entropy(bits, 7776),
+        Passphraser::MIN_ENTROPY_WORDS_MAX
+      );
+    }
+
+    let mut builder = Passphraser::new(2);
+    let passphrase = builder.min_entropy(100.0).generate();
+
+    assert_eq!(builder.word_count(), 8);
+    assert_eq!(passphrase.words().len(), 8);
+    assert!(passphrase.entropy().entropy >= 100.0);
+
+    builder.min_entropy(f32::INFINITY);
+
+    assert_eq!(builder.word_count(), Passphraser::MIN_ENTROPY_WORDS_MAX);
+    assert_eq!(
+      builder.prefer_short(true).word_count(),
+      Passphraser::MIN_ENTROPY_WORDS_MAX
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_target_bits() {
+    let passphrase = Passphraser::new(2).target_bits(70.0).generate();
+    let entropy = passphrase.entropy();
+
+    assert_eq!(passphrase.words().len(), 6);
+    assert_eq!(entropy.target, Some(70.0));
+    assert_eq!(entropy.overshoot(), Some(calc_entropy(7776, 6) - 70.0));
+    assert_eq!(Entropy::new(7776, 6).overshoot(), None);
+
+    let wordlist = parse_wordlist("foo\nbar\nbaz\nqux\nquux");
+    let mut builder = Passphraser::new(1);
+    builder
+      .wordlist(&wordlist)
+      .unique_words(true)
+      .target_bits(6.0);
+
+    // Unique words add 2.32, 2, 1.58 and 1 bits, so 3 words fall short of the target.
+    let passphrase = builder.generate();
+    let entropy = passphrase.entropy();
+
+    assert_eq!(passphrase.words().len(), 4);
+    assert!(entropy.entropy >= 6.0);
+    assert!(calc_entropy_no_replacement(5, 3) < 6.0);
+    assert_eq!(
+      entropy.overshoot(),
+      Some(calc_entropy_no_replacement(5, 4) - 6.0)
+    );
+
+    builder.target_bits(100.0);
+
+    assert_eq!(builder.generate().words().len(), 5);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_expected_entropy() {
+    let mut builder = Passphraser::new(6);
+
+    assert_eq!(builder.expected_entropy(), Entropy::new(7776, 6));
+    assert_eq!(
+      builder.expected_entropy(),
+      builder.generate().entropy().clone()
+    );
+
+    builder.exclude(&["a".to_string()]).numbers(2);
+
+    assert_eq!(
+      builder.expected_entropy(),
+      builder.generate().entropy().clone()
+    );
+    assert!(builder.expected_entropy().possibilities < 7776);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_min_chars() {
+    let mut builder = Passphraser::new(2);
+    builder.seed(42).preset(Preset::KebabCase).min_chars(60);
+
+    let passphrase = builder.generate();
+    let count = passphrase.words().len();
+
+    assert!(count > 2);
+    assert!(passphrase.format().len() >= 60);
+    assert!(passphrase.words()[..count - 1].join("-").len() < 60);
+    assert_eq!(passphrase.entropy(), &Entropy::new(7776, count));
+    assert_eq!(
+      passphrase.words()[..2],
+      builder.min_chars(0).generate().words()[..]
+    );
+
+    let passphrase = builder.min_chars(usize::MAX).generate();
+
+    assert_eq!(passphrase.words().len(), Passphraser::MIN_CHARS_WORDS_MAX);
+
+    builder
+      .length(5)
+      .min_chars(0)
+      .numbers(2)
+      .symbols("!")
+      .prefix("<<")
+      .suffix(">")
+      .group_size(2)
+      .group_delimiter(" / ");
+
+    let delimiters = Preset::Delimiters(vec![".".to_string(), "--".to_string()]);
+
+    for preset in [
+      Preset::Default,
+      Preset::PascalCase,
+      Preset::RandomCase,
+      delimiters,
+    ] {
+      let passphrase = builder.preset(preset).generate();
+
+      assert_eq!(
+        builder.formatted_len(passphrase.words()),
+        passphrase.format().chars().count()
+      );
+    }
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_exclude() {
+    let exclude = ["a".to_string(), "e".to_string()];
+    let remaining = builtin_wordlist()
+      .iter()
+      .filter(|line| !line.contains(['a', 'e']))
+      .count();
+
+    let mut builder = Passphraser::new(10);
+    builder.exclude(&exclude);
+
+    for _ in 0..100 {
+      let passphrase = builder.generate_checked().unwrap();
+
+      assert_eq!(passphrase.words().len(), 10);
+      assert!(passphrase
+        .words()
+        .iter()
+        .all(|word| !word.contains(['a', 'e'])));
+      assert_eq!(passphrase.entropy().possibilities, remaining);
+    }
+
+    let result = builder.exclude(&["".to_string()]).generate_checked();
+
+    assert!(matches!(result, Err(GenerateError::EmptyWordlist)));
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_banlist() {
+    let mut builder = Passphraser::new(20);
+    builder.banlist(&["Abacus".to_string(), "zoom".to_string()]);
+
+    for _ in 0..50 {
+      let passphrase = builder.generate();
+
+      assert!(passphrase
+        .words()
+        .iter()
+        .all(|word| word != "abacus" && word != "zoom"));
+      assert_eq!(passphrase.rolls().len(), 20);
+    }
+
+    assert_eq!(builder.expected_entropy().possibilities, 7774);
+
+    let wordlist = ["11 foo", "12 bar"].map(String::from);
+    let passphrase = builder
+      .wordlist(&wordlist)
+      .dice_count(2)
+      .dice_faces(2)
+      .banlist(&["foo".to_string()])
+      .generate();
+
+    assert!(passphrase.words().iter().all(|word| word == "bar"));
+    let banned = wordlist.map(|line| line[3..].to_string());
+
+    assert_eq!(
+      builder.banlist(&banned).generate_checked().unwrap_err(),
+      GenerateError::EmptyWordlist
+    );
+
+    let wordlist = ["11 foo", "12 bar", "33 baz"].map(String::from);
+    builder.wordlist(&wordlist);
+
+    assert_eq!(builder.expected_entropy().possibilities, 0);
+    assert_eq!(
+      builder.generate_checked().unwrap_err(),
+      GenerateError::EmptyWordlist
+    );
+
+    let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+    builder.banlist(&banned[..1]);
+
+    assert_eq!(
+      builder.generate_checked_with(&mut rng).unwrap_err(),
+      GenerateError::RerollsExhausted
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_checksum() {
+    let mut builder = Passphraser::new(6);
+    builder.seed(42);
+
+    let plain = builder.generate();
+    let passphrase = builder.checksum(true).generate();
+
+    assert_eq!(passphrase.words().len(), 7);
+    assert_eq!(passphrase.words()[..6], plain.words()[..]);
+    assert_eq!(passphrase.entropy(), plain.entropy());
+
+    let mut words = plain.words().to_vec();
+    let check_word = builder.check_word(&words).unwrap();
+
+    assert_eq!(passphrase.words()[6], check_word);
+
+    words[2] = if words[2] == "abacus" {
+      "abdomen"
+    } else {
+      "abacus"
+    }
+    .to_string();
+
+    assert_ne!(builder.check_word(&words).unwrap(), check_word);
+
+    words[2] = "typo".to_string();
+
+    assert_eq!(builder.check_word(&words), None);
+  }
+
+  #[test]
+  fn test_parse_rolls() {
+    assert_eq!(
+      parse_rolls(" 11111\n23456 ", 5, 6),
+      Ok(vec![vec![1, 1, 1, 1, 1], vec![2, 3, 4, 5, 6]])
+    );
+    assert_eq!(parse_rolls("", 5, 6), Ok(Vec::new()));
+    assert_eq!(
+      parse_rolls("11111 1111", 5, 6),
+      Err(RollsError::Length {
+        position: 2,
+        group: "1111".to_string(),
+        expected: 5
+      })
+    );
+    assert_eq!(
+      parse_rolls("11117", 5, 6),
+      Err(RollsError::OutOfRange {
+        position: 1,
+        group: "11117".to_string(),
+        faces: 6
+      })
+    );
+    assert!(parse_rolls("1111a", 5, 6).is_err());
+    assert!(parse_rolls("11110", 5, 6).is_err());
+  }
+
+  #[test]
+  fn test_parse_rolls_formats() {
+    let expected = Ok(vec![vec![1, 1, 1, 1, 1], vec![2, 3, 4, 5, 6]]);
+
+    for input in [
+      "1-1-1-1-1,2-3-4-5-6",
+      "1-1-1-1-1, 2-3-4-5-6",
+      "11111;23456",
+      "1.1.1.1.1\n2.3.4.5.6\n",
+      "11111\r\n\r\n23456",
+      "111-11 23-456,",
+    ] {
+      assert_eq!(parse_rolls(input, 5, 6), expected, "input {input:?}");
+    }
+
+    let err = parse_rolls("1-1-1-1-1,2-3-4-5,6-6-6-6-6", 5, 6).unwrap_err();
+
+    assert_eq!(
+      err,
+      RollsError::Length {
+        position: 2,
+        group: "2-3-4-5".to_string(),
+        expected: 5
+      }
+    );
+    assert_eq!(
+      err.to_string(),
+      "roll 2 (`2-3-4-5`) must have exactly 5 digits"
+    );
+    assert_eq!(
+      parse_rolls("11111\n22222\n3-3-3-3-7", 5, 6),
+      Err(RollsError::OutOfRange {
+        position: 3,
+        group: "3-3-3-3-7".to_string(),
+        faces: 6
+      })
+    );
+    assert!(parse_rolls("1+1+1+1+1", 5, 6).is_err());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_generate_from_rolls() {
+    let builder = Passphraser::new(6);
+    let rolls = builder.parse_rolls("11111 23456 66666").unwrap();
+    let passphrase = builder.generate_from_rolls(rolls).unwrap();
+
+    assert_eq!(passphrase.words(), ["abacus", "dispatch", "zoom"]);
+    assert_eq!(passphrase.entropy(), &Entropy::new(7776, 3));
+
+    for roll in [vec![7, 7, 7, 7, 7], vec![0, 1, 1, 1, 1], vec![1, 1, 1, 1]] {
+      assert_eq!(
+        builder.generate_from_rolls(vec![roll.clone()]).unwrap_err(),
+        GenerateError::InvalidRoll(roll)
+      );
+    }
+
+    let mut builder = Passphraser::new(1);
+    builder
+      .wordlist(&["11 foo".to_string()])
+      .dice_count(2)
+      .index_scheme(IndexScheme::Positional);
+
+    assert_eq!(
+      builder.generate_from_rolls(vec![vec![0, 1]]).unwrap_err(),
+      GenerateError::InvalidRoll(vec![0, 1])
+    );
+    assert_eq!(
+      builder.generate_from_rolls(vec![vec![1, 2]]).unwrap_err(),
+      GenerateError::MissingIndex(1)
+    );
+    assert_eq!(IndexScheme::Positional.index(&[0], 6), usize::MAX);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_rolls_compact() {
+    let builder = Passphraser::new(6);
+    let passphrase = builder.generate();
+    let compact = passphrase.rolls_compact();
+
+    assert_eq!(compact.len(), 30);
+    assert!(compact.chars().all(|c| ('0'..='5').contains(&c)));
+
+    let restored = builder.from_rolls_compact(&compact).unwrap();
+
+    assert_eq!(restored.words(), passphrase.words());
+    assert_eq!(restored.rolls(), passphrase.rolls());
+
+    let rolls = builder.parse_rolls_compact("0000012345").unwrap();
+
+    assert_eq!(rolls, [vec![1, 1, 1, 1, 1], vec![2, 3, 4, 5, 6]]);
+    assert_eq!(
+      builder.parse_rolls_compact("000001234"),
+      Err(RollsError::Length {
+        position: 2,
+        group: "1234".to_string(),
+        expected: 5
+      })
+    );
+    assert_eq!(
+      builder.parse_rolls_compact("00006"),
+      Err(RollsError::InvalidDigit {
+        digit: '6',
+        faces: 6
+      })
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_rolls() {
+    let mut builder = Passphraser::new(6);
+    builder.seed(42);
+
+    let generated = builder.generate();
+
+    assert_eq!(generated.rolls().len(), 6);
+    assert_eq!(
+      passphrase(&builtin_wordlist(), generated.rolls().to_vec()),
+      generated.words()
+    );
+
+    let shuffled = builder.shuffle(true).checksum(true).generate();
+
+    assert_eq!(shuffled.rolls().len(), 6);
+    assert_eq!(
+      passphrase(&builtin_wordlist(), shuffled.rolls().to_vec()),
+      shuffled.words()[..6]
+    );
+
+    let excluded = builder.exclude(&["a".to_string()]).generate();
+
+    assert!(excluded.rolls().is_empty());
+    assert!(excluded.indices().is_empty());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_indices() {
+    let generated = Passphraser::new(6).seed(42).shuffle(true).generate();
+    let entries = to_entries(&builtin_wordlist());
+
+    assert_eq!(generated.indices().len(), 6);
+
+    for (index, word) in generated.indices().iter().zip(generated.words()) {
+      assert_eq!(&entries[index], word);
+    }
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_iter() {
+    let mut builder = Passphraser::new(6);
+    let passphrases = builder.seed(42).iter().take(3).collect::<Vec<_>>();
+
+    assert_eq!(passphrases.len(), 3);
+    assert_eq!(passphrases[0].words(), builder.generate().words());
+    assert_ne!(passphrases[0].words(), passphrases[1].words());
+    assert_ne!(passphrases[1].words(), passphrases[2].words());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_plain_wordlist() {
+    let wordlist = ["foo", "bar", "", "baz", "qux"].map(String::from);
+    let passphrase = Passphraser::new(10)
+      .wordlist(&wordlist)
+      .generate_checked()
+      .unwrap();
+
+    assert_eq!(passphrase.words().len(), 10);
+    assert!(passphrase
+      .words()
+      .iter()
+      .all(|word| wordlist.contains(word) && !word.is_empty()));
+    assert_eq!(passphrase.entropy().possibilities, 4);
+    assert_eq!(passphrase.entropy().entropy, 20.0);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_possibilities() {
+    let wordlist = ["11 foo", "12 bar", "", "13 baz", "oops", "14 foo"].map(String::from);
+    let passphrase = Passphraser::new(4)
+      .wordlist(&wordlist)
+      .dice_count(2)
+      .dice_faces(4)
+      .generate();
+
+    assert_eq!(passphrase.entropy().possibilities, 3);
+
+    let wordlist = ["foo", "bar", "", "foo"].map(String::from);
+    let passphrase = Passphraser::new(4).wordlist(&wordlist).generate();
+
+    assert_eq!(passphrase.entropy().possibilities, 2);
+
+    let wordlist = ["a", "a", "a", "b"].map(String::from);
+    let passphrase = Passphraser::new(1000)
+      .wordlist(&wordlist)
+      .seed(42)
+      .generate();
+    let picked = passphrase
+      .words()
+      .iter()
+      .filter(|word| *word == "a")
+      .count();
+
+    assert_eq!(passphrase.entropy().possibilities, 2);
+    assert!((400..600).contains(&picked));
+
+    let mut builder = Passphraser::new(4);
+    assert_eq!(builder.expected_entropy().possibilities, 7776);
+
+    builder.dice_faces(5);
+    assert_eq!(builder.expected_entropy().possibilities, 3125);
+
+    builder.dice_faces(6).banlist(&["abacus".to_string()]);
+    assert_eq!(builder.expected_entropy().possibilities, 7775);
+
+    builder.wordlist(&wordlist);
+    assert_eq!(builder.expected_entropy().possibilities, 2);
+
+    builder.reset();
+    assert_eq!(builder.expected_entropy().possibilities, 7776);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_seed() {
+    let first = Passphraser::new(6).seed(42).generate();
+    let second = Passphraser::new(6).seed(42).generate();
+    let third = Passphraser::new(6).seed(43).generate();
+
+    assert_eq!(first.words(), second.words());
+    assert_ne!(first.words(), third.words());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_generate_with() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let seeded = Passphraser::new(6).seed(42).generate();
+    let injected = Passphraser::new(6).generate_with(&mut rng);
+
+    assert_eq!(seeded.words(), injected.words());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_generate_checked() {
+    let passphrase = Passphraser::new(6).generate_checked().unwrap();
+
+    assert_eq!(passphrase.words().len(), 6);
+
+    let wordlist = vec!["11111 abacus".to_string()];
+    let result = Passphraser::new(1)
+      .wordlist(&wordlist)
+      .seed(1)
+      .generate_checked();
+
+    assert!(matches!(result, Err(GenerateError::MissingIndex(index)) if index != 11111));
+  }
+
+  #[test]
+  fn test_passphrase() {
+    let wordlist = ["11111 abacus", "11112 abdomen", "oops", "11111 duplicate"].map(String::from);
+    let words = passphrase(
+      &wordlist,
+      vec![vec![1, 1, 1, 1, 2], vec![6, 6, 6, 6, 6], vec![1; 5]],
+    );
+
+    assert_eq!(words, ["abdomen", "abacus"]);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_numbers() {
+    let plain = Passphraser::new(6).seed(42).generate();
+    let passphrase = Passphraser::new(6).seed(42).numbers(3).generate();
+
+    let formatted = passphrase.format();
+    let (words, numbers) = formatted.split_at(formatted.len() - 3);
+
+    assert!(numbers.chars().all(|c| c.is_ascii_digit()));
+    assert_eq!(words, plain.format());
+    assert_eq!(
+      passphrase.entropy().entropy,
+      plain.entropy().entropy + 10f32.log2() * 3.0
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_symbols() {
+    let plain = Passphraser::new(6).seed(42).generate();
+    let passphrase = Passphraser::new(6).seed(42).symbols("!!?").generate();
+
+    let formatted = passphrase.format();
+    let symbols = formatted
+      .chars()
+      .filter(|c| !c.is_alphabetic() && *c != ' ');
+
+    assert_eq!(symbols.collect::<String>().len(), 1);
+    assert_eq!(formatted.replace(['!', '?'], ""), plain.format());
+    assert_eq!(passphrase.entropy().entropy, plain.entropy().entropy + 1.0);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_policy() {
+    let mut builder = Passphraser::new(6);
+    builder.policy(Policy::STRICT).preset(Preset::LowerCase);
+
+    for _ in 0..100 {
+      let passphrase = builder.generate().format();
+
+      assert!(passphrase.chars().any(char::is_uppercase));
+      assert!(passphrase.chars().any(|c| c.is_ascii_digit()));
+      assert!(passphrase
+        .chars()
+        .any(|c| Passphraser::SYMBOLS_DEFAULT.contains(c)));
+    }
+
+    let expected = calc_entropy(7776, 6) + calc_entropy(10, 1) + calc_entropy(8, 1);
+
+    assert_eq!(builder.expected_entropy().entropy, expected);
+
+    builder.numbers(3).symbols("!");
+
+    let expected = calc_entropy(7776, 6) + calc_entropy(10, 3);
+
+    assert_eq!(builder.expected_entropy().entropy, expected);
+    assert_eq!(to_uppercased_once("1-foo-bar"), "1-Foo-bar");
+    assert_eq!(to_uppercased_once("foo-Bar"), "foo-Bar");
+    assert_eq!(to_uppercased_once("123"), "123");
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_random_case() {
+    let plain = Passphraser::new(16).seed(42).generate();
+    let first = Passphraser::new(16)
+      .seed(42)
+      .preset(Preset::RandomCase)
+      .generate();
+    let second = Passphraser::new(16)
+      .seed(42)
+      .preset(Preset::RandomCase)
+      .generate();
+
+    let formatted = first.format();
+
+    assert_eq!(formatted, second.format());
+    assert_eq!(formatted.to_lowercase(), plain.format());
+    assert!(formatted
+      .split(' ')
+      .any(|word| word == to_capitalized(word)));
+    assert!(formatted
+      .split(' ')
+      .any(|word| word != to_capitalized(word)));
+    assert_eq!(first.entropy().entropy, plain.entropy().entropy + 16.0);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_display() {
+    let passphrase = Passphraser::new(6).preset(Preset::KebabCase).generate();
+
+    assert_eq!(passphrase.to_string(), passphrase.format());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_capitalize() {
+    let plain = Passphraser::new(4).seed(42).generate();
+    let passphrase = Passphraser::new(4)
+      .seed(42)
+      .preset(Preset::KebabCase)
+      .capitalize(true)
+      .generate();
+
+    let expected = plain
+      .words()
+      .iter()
+      .map(|word| to_capitalized(word))
+      .collect::<Vec<_>>()
+      .join("-");
+
+    assert_eq!(passphrase.format(), expected);
+  }
+
+  #[test]
+  fn test_to_capitalized() {
+    assert_eq!(to_capitalized(""), "");
+    assert_eq!(to_capitalized("fOO"), "FOO");
+    assert_eq!(to_capitalized("élan"), "Élan");
+
+    assert_eq!(to_title_cased(""), "");
+    assert_eq!(to_title_cased("fOO"), "Foo");
+    assert_eq!(to_title_cased("éCOLE"), "École");
+    assert_eq!(to_title_cased("ΟΔΟΣ"), "Οδος");
+  }
+
+  #[test]
+  fn test_normalize_case() {
+    let mut passphrase = passphrase_of(&["fOO", "bAR"]);
+    passphrase.preset = Preset::KebabCase;
+    passphrase.capitalize = true;
+
+    assert_eq!(passphrase.format(), "FOO-BAR");
+
+    passphrase.normalize_case = true;
+
+    assert_eq!(passphrase.format(), "Foo-Bar");
+    assert_eq!(passphrase.format_with(&Preset::CamelCase), "fooBar");
+  }
+
+  #[test]
+  fn test_from_words() {
+    let words = ["correct", "horse", "battery", "staple"]
+      .map(String::from)
+      .to_vec();
+    let passphrase = Passphrase::from_words(words.clone(), 7776, Preset::KebabCase);
+
+    assert_eq!(passphrase.words(), words);
+    assert_eq!(passphrase.entropy(), &Entropy::new(7776, 4));
+    assert_eq!(passphrase.format(), "correct-horse-battery-staple");
+    assert_eq!(
+      passphrase.format_with(&Preset::PascalCase),
+      "CorrectHorseBatteryStaple"
+    );
+    assert_eq!(
+      passphrase.format_with(&Preset::SnakeCase),
+      "correct_horse_battery_staple"
+    );
+    assert_eq!(
+      passphrase.format_with(&Preset::TitleCase),
+      "Correct Horse Battery Staple"
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_diceware_error() {
+    let err = DicewareError::from(GenerateError::MissingIndex(11111));
+
+    assert_eq!(
+      err,
+      DicewareError::Generate(GenerateError::MissingIndex(11111))
+    );
+    assert_eq!(
+      err.to_string(),
+      GenerateError::MissingIndex(11111).to_string()
+    );
+
+    let wordlist_err = WordlistError {
+      missing: vec![11, 13],
+      duplicates: vec![12, 21],
+    };
+    let err = DicewareError::from(wordlist_err.clone());
+
+    assert_eq!(err, DicewareError::Wordlist(wordlist_err.clone()));
+    assert_eq!(err.to_string(), wordlist_err.to_string());
+
+    let rolls_err = parse_rolls("17", 2, 6).unwrap_err();
+    let err = DicewareError::from(rolls_err.clone());
+
+    assert_eq!(err.to_string(), rolls_err.to_string());
+    assert!(err.source().is_none());
+
+    let run = || -> Result<Passphrase, DicewareError> {
+      let passphrase = Passphraser::new(0).generate_checked()?;
+      Ok(passphrase)
+    };
+
+    assert_eq!(
+      run().unwrap_err(),
+      DicewareError::Generate(GenerateError::ZeroLength)
+    );
+  }
+
+  #[test]
+  fn test_preset_from_name() {
+    assert!(matches!(
+      Preset::try_from_name("pascal"),
+      Some(Preset::PascalCase)
+    ));
+    assert!(matches!("title".parse(), Ok(Preset::TitleCase)));
+    assert!(Preset::try_from_name("pascl").is_none());
+
+    let err = "pascl".parse::<Preset>().unwrap_err();
+
+    assert_eq!(err.name, "pascl");
+    assert_eq!(
+      err.to_string(),
+      "unknown preset `pascl`, expected one of: pascal, camel, kebab, snake, title, upper, lower"
+    );
+
+    for name in Preset::NAMES {
+      assert!(Preset::try_from_name(name).is_some());
+    }
+
+    #[allow(deprecated)]
+    let preset = Preset::from("pascl");
+
+    assert!(matches!(preset, Preset::Default));
+  }
+
+  #[test]
+  fn test_format_capitalize_positions() {
+    let passphrase = passphrase_of(&["correct", "horse", "battery", "staple"]);
+
+    assert_eq!(
+      passphrase.format_with(&Preset::CapitalizePositions(vec![0, 2])),
+      "Correct horse Battery staple"
+    );
+    assert_eq!(
+      passphrase.format_with(&Preset::CapitalizePositions(vec![3, 10])),
+      "correct horse battery Staple"
+    );
+  }
+
+  #[test]
+  fn test_format_upper_and_lower_case() {
+    let passphrase = passphrase_of(&["Foo", "BAR"]);
+
+    assert_eq!(passphrase.format_with(&Preset::UpperCase), "FOO BAR");
+    assert_eq!(passphrase.format_with(&Preset::LowerCase), "foo bar");
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_bits_per_word() {
+    let entropy = Passphraser::new(6).generate().entropy().clone();
+
+    assert!((entropy.bits_per_word() * 6.0 - entropy.entropy).abs() < 1e-4);
+    assert_eq!(Entropy::new(2048, 1).bits_per_word(), 11.0);
+  }
+
+  #[test]
+  fn test_strength() {
+    assert_eq!(Strength::from_entropy(0.0), Strength::Weak);
+    assert_eq!(Strength::from_entropy(39.9), Strength::Weak);
+    assert_eq!(Strength::from_entropy(40.0), Strength::Fair);
+    assert_eq!(Strength::from_entropy(60.0), Strength::Strong);
+    assert_eq!(Strength::from_entropy(79.9), Strength::Strong);
+    assert_eq!(Strength::from_entropy(80.0), Strength::Excellent);
+    assert_eq!(
+      Strength::from_entropy_with(50.0, [10.0, 20.0, 50.0]),
+      Strength::Excellent
+    );
+
+    assert_eq!(passphrase_of(&["foo"; 4]).strength(), Strength::Fair);
+    assert_eq!(passphrase_of(&["foo"; 6]).strength(), Strength::Strong);
+    assert_eq!(passphrase_of(&["foo"; 7]).strength(), Strength::Excellent);
+    assert_eq!(Strength::Excellent.to_string(), "excellent");
+  }
+
+  #[test]
+  #[cfg(feature = "kdf")]
+  fn test_from_key() {
+    let builder = Passphraser::new(6);
+    let passphrase = builder.from_key(b"master secret", b"example.com").format();
+
+    assert_eq!(
+      passphrase,
+      builder.from_key(b"master secret", b"example.com").format()
+    );
+    assert_ne!(
+      passphrase,
+      builder.from_key(b"master secret", b"example.org").format()
+    );
+    // Pinned, so that changes of the derivation scheme are noticed.
+    assert_eq!(
+      passphrase,
+      "unshackle delete unbounded navigator tweezers reaction"
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_template() {
+    let template = Template::new("Wwww-dd-W\\d\\W\\");
+
+    assert_eq!(template.word_count(), 5);
+    assert_eq!(template.digit_count(), 2);
+
+    let words = ["foo", "BAR", "baz", "qux", "quux", "extra"].map(String::from);
+    let rendered = template.render(&words, "123", to_capitalized);
+
+    assert_eq!(rendered, "Foobarbazqux-12-QuuxdW\\ extra3");
+
+    let mut builder = Passphraser::new(0);
+    builder.template("W.w.d").numbers(1);
+
+    let passphrase = builder.generate_checked().unwrap();
+    let formatted = passphrase.format();
+    let parts = formatted.split('.').collect::<Vec<_>>();
+
+    assert_eq!(passphrase.words().len(), 2);
+    assert_eq!(parts.len(), 3);
+    assert!(parts[0].starts_with(|c: char| c.is_uppercase()));
+    assert!(parts[2].len() == 2 && parts[2].chars().all(|c| c.is_ascii_digit()));
+    assert_eq!(
+      passphrase.entropy().entropy,
+      calc_entropy(7776, 2) + calc_entropy(10, 2)
+    );
+  }
+
+  #[test]
+  fn test_memorability() {
+    let easy = passphrase_of(&["cat", "dog", "sun"]);
+    let hard = passphrase_of(&["strengths", "rhythms", "twelfths"]);
+
+    assert_eq!(easy.memorability(), 1.0);
+    assert!(hard.memorability() < 0.3);
+    assert_eq!(hard.memorability(), hard.memorability());
+    assert_eq!(passphrase_of(&[]).memorability(), 0.0);
+    assert_eq!(word_memorability("banana"), word_memorability("BANANA"));
+  }
+
+  #[test]
+  fn test_acronym() {
+    let passphrase = passphrase_of(&["correct", "horse", "battery", "staple"]);
+
+    assert_eq!(passphrase.acronym(), "CHBS");
+    assert_eq!(passphrase_of(&["élan", "über"]).acronym(), "ÉÜ");
+    assert_eq!(passphrase_of(&[]).acronym(), "");
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_shuffle() {
+    let mut builder = Passphraser::new(6);
+    builder.seed(42);
+
+    let plain = builder.generate();
+    let shuffled = builder.shuffle(true).generate();
+    let again = builder.generate();
+
+    assert_eq!(shuffled.words(), again.words());
+    assert_ne!(shuffled.words(), plain.words());
+
+    let mut sorted = shuffled.words().to_vec();
+    sorted.sort();
+
+    let mut expected = plain.words().to_vec();
+    expected.sort();
+
+    assert_eq!(sorted, expected);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_normalize_wordlist() {
+    let wordlist = parse_wordlist("11\tApple\n12\tBANANA\n21\tcherry\n22\tDaTe");
+    let mut builder = Passphraser::new(8);
+    builder.wordlist(&wordlist).dice_count(2).dice_faces(2);
+
+    let lowercased = |builder: &Passphraser| {
+      builder
+        .generate_checked()
+        .unwrap()
+        .words()
+        .iter()
+        .all(|word| *word == word.to_lowercase())
+    };
+
+    builder.normalize_wordlist(true);
+    assert!(lowercased(&builder));
+    assert_eq!(
+      builder.ordered_words(),
+      ["apple", "banana", "cherry", "date"]
+    );
+
+    builder.wordlist(&wordlist);
+    assert!(lowercased(&builder));
+
+    builder.normalize_wordlist(false).wordlist(&wordlist);
+    assert_eq!(
+      builder.ordered_words(),
+      ["Apple", "BANANA", "cherry", "DaTe"]
+    );
+  }
+
+  #[test]
+  fn test_concat() {
+    let mut first = passphrase_of(&["foo", "bar"]);
+    first.symbol = Some((1, '!'));
+    first.numbers = "1".to_string();
+
+    let mut second = passphrase_of(&["baz", "qux", "quux"]);
+    second.symbol = Some((0, '?'));
+    second.numbers = "2".to_string();
+
+    let entropy = first.entropy().entropy + second.entropy().entropy;
+    let combined = first.concat(second);
+
+    assert_eq!(combined.words().len(), 5);
+    assert_eq!(combined.entropy().entropy, entropy);
+    assert_eq!(combined.entropy().possibilities, 7776);
+    assert_eq!(combined.format(), "foo bar! baz? qux quux12");
+
+    let second = passphrase_of(&["baz"]);
+    let mut first = passphrase_of(&["foo"]);
+    first.rolls = vec![vec![1, 1, 1, 1, 1]];
+
+    assert!(first.concat(second).rolls().is_empty());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_concat_random_delimiters() {
+    let mut builder = Passphraser::new(3);
+    builder.random_delimiters(&["-", "."].map(String::from));
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let first = builder.generate_with(&mut rng);
+    let second = builder.generate_with(&mut rng);
+
+    let formatted = format!("{} {}", first.format(), second.format());
+    let entropy = first.entropy().entropy + second.entropy().entropy;
+    let combined = first.concat(second);
+
+    assert_eq!(combined.format(), formatted);
+    assert_eq!(combined.entropy().entropy, entropy);
+    assert_eq!(entropy, calc_entropy(7776, 6) + calc_entropy(2, 4));
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_reorder() {
+    let mut passphrase = passphrase_of(&["foo", "bar", "baz", "check"]);
+    passphrase.symbol = Some((0, '!'));
+    passphrase.checksum = true;
+    passphrase.reverse();
+
+    assert_eq!(passphrase.words(), ["baz", "bar", "foo", "check"]);
+    assert_eq!(passphrase.format(), "baz bar foo! check");
+
+    let mut first = passphrase_of(&["foo", "bar", "baz", "qux"]);
+    let mut second = passphrase_of(&["foo", "bar", "baz", "qux"]);
+    first.shuffle(&mut StdRng::seed_from_u64(42));
+    second.shuffle(&mut StdRng::seed_from_u64(42));
+
+    assert_eq!(first.words(), second.words());
+  }
+
+  #[test]
+  fn test_format_camel_case() {
+    let passphrase = passphrase_of(&["Correct", "horse", "battery", "staple"]);
+
+    assert_eq!(
+      passphrase.format_with(&Preset::CamelCase),
+      "correctHorseBatteryStaple"
+    );
+  }
+
+  #[test]
+  fn test_format_title_case() {
+    let passphrase = passphrase_of(&["foo", "bar"]);
+
+    assert_eq!(passphrase.format_with(&Preset::TitleCase), "Foo Bar");
+  }
+
+  #[test]
+  #[cfg(feature = "serde")]
+  fn test_serde_entropy() {
+    let entropy = Entropy::new(7776, 6);
+
+    let json = serde_json::to_string(&entropy).unwrap();
+    let parsed = serde_json::from_str::<Entropy>(&json).unwrap();
+
+    assert_eq!(parsed.possibilities, entropy.possibilities);
+    assert_eq!(parsed.entropy, entropy.entropy);
+  }
+
+  #[test]
+  #[cfg(feature = "serde")]
+  fn test_serde_passphrase() {
+    let mut passphrase = passphrase_of(&["correct", "horse"]);
+    passphrase.preset = Preset::KebabCase;
+
+    assert_eq!(
+      serde_json::to_string(&passphrase).unwrap(),
+      r#"{"passphrase":"correct-horse","words":["correct","horse"],"preset":"KebabCase","entropy":{"possibilities":7776,"entropy":25.849625}}"#
+    );
+  }
+
+  #[test]
+  #[cfg(all(feature = "serde", feature = "thread-rng"))]
+  fn test_serde_with_formats() {
+    let passphrase = Passphraser::new(2).seed(42).generate();
+
+    assert_eq!(
+      serde_json::to_string(&passphrase.with_formats()).unwrap(),
+      r#"{"passphrase":"persuaded starfish","words":["persuaded","starfish"],"preset":"Default","entropy":{"possibilities":7776,"entropy":25.849625},"formats":{"pascal":"PersuadedStarfish","camel":"persuadedStarfish","kebab":"persuaded-starfish","snake":"persuaded_starfish","title":"Persuaded Starfish","upper":"PERSUADED STARFISH","lower":"persuaded starfish"}}"#
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_parse_wordlist() {
+    let wordlist = parse_wordlist("11111 foo\n11112 bar\n");
+
+    assert_eq!(wordlist, ["11111 foo", "11112 bar"]);
+    assert_eq!(parse_wordlist(EFF_WORDLIST), builtin_wordlist());
+
+    let passphrase = Passphraser::new(3)
+      .wordlist(&parse_wordlist("foo\nbar"))
+      .generate();
+
+    assert!(passphrase
+      .words()
+      .iter()
+      .all(|word| word == "foo" || word == "bar"));
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_read_wordlist_comments() {
+    let path = concat!(
+      env!("CARGO_MANIFEST_DIR"),
+      "/tests/fixtures/wordlist_comments.txt"
+    );
+    let wordlist = read_wordlist(path).unwrap();
+
+    assert_eq!(wordlist, ["11\tfoo", "12\tbar", "21\tbaz", "22\tqux"]);
+
+    let lines = ["# header", "", "foo", "  # indented", "bar", ""].map(String::from);
+    let mut builder = Passphraser::new(100);
+    builder.wordlist(&lines);
+
+    let passphrase = builder.generate();
+
+    assert_eq!(passphrase.entropy().possibilities, 2);
+    assert!(passphrase
+      .words()
+      .iter()
+      .all(|word| word == "foo" || word == "bar"));
+
+    let indexed = ["# 11 nope", "11 foo", "", "12 bar"].map(String::from);
+
+    assert_eq!(to_entries(&indexed).len(), 2);
+    assert!(duplicate_words(&indexed).is_empty());
+    assert!(Passphraser::new(1)
+      .wordlist(&indexed)
+      .dice_count(2)
+      .dice_faces(2)
+      .validate()
+      .is_err_and(|err| err.missing == [21, 22] && err.duplicates.is_empty()));
+  }
+
+  #[test]
+  fn test_parse_entries() {
+    let text = concat!(
+      "# comment\n11111 abacus\r\n11112:abdomen\n\nabnormal\n",
+      "1111x abide\n11113\n \t11114\tabiding extra\n11111 again",
+    );
+
+    assert_eq!(
+      parse_entries(text),
+      [
+        WordlistEntry {
+          index: 11111,
+          word: "abacus".to_string()
+        },
+        WordlistEntry {
+          index: 11112,
+          word: "abdomen".to_string()
+        },
+        WordlistEntry {
+          index: 11114,
+          word: "abiding".to_string()
+        },
+        WordlistEntry {
+          index: 11111,
+          word: "again".to_string()
+        },
+      ]
+    );
+    assert_eq!(WordlistEntry::parse("# 11111 abacus"), None);
+    assert_eq!(WordlistEntry::parse("-1 abacus"), None);
+    assert_eq!(parse_entries(EFF_WORDLIST).len(), 7776);
+  }
+
+  #[test]
+  fn test_concat_wordlists() {
+    let fixture = |name: &str| {
+      read_wordlist(format!(
+        "{}/tests/fixtures/{name}",
+        env!("CARGO_MANIFEST_DIR")
+      ))
+      .unwrap()
+    };
+
+    let comments = fixture("wordlist_comments.txt");
+    let extra = fixture("wordlist_extra.txt");
+    let wordlist = concat_wordlists(&[comments.clone(), extra]).unwrap();
+
+    assert_eq!(wordlist.len(), 9);
+    assert!(Passphraser::new(1)
+      .wordlist(&wordlist)
+      .dice_count(2)
+      .dice_faces(3)
+      .validate()
+      .is_ok());
+
+    let crlf = fixture("wordlist_crlf.txt");
+
+    assert_eq!(
+      concat_wordlists(&[comments, crlf]),
+      Err(WordlistError {
+        missing: vec![],
+        duplicates: vec![11, 12, 21, 22],
+      })
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_read_wordlist_crlf() {
+    let path = concat!(
+      env!("CARGO_MANIFEST_DIR"),
+      "/tests/fixtures/wordlist_crlf.txt"
+    );
+    let wordlist = read_wordlist(path).unwrap();
+
+    assert_eq!(wordlist, ["11\tfoo", "12\tbar", "21\tbaz", "22\tqux"]);
+
+    let passphrase = Passphraser::new(4)
+      .wordlist(&wordlist)
+      .dice_count(2)
+      .dice_faces(2)
+      .generate();
+
+    assert!(passphrase
+      .words()
+      .iter()
+      .all(|word| ["foo", "bar", "baz", "qux"].contains(&word.as_str())));
+
+    let raw = ["11\tfoo\r".to_string(), "12\tbar\r".to_string()];
+
+    assert_eq!(to_entries(&raw).get(&11).map(String::as_str), Some("foo"));
+    assert_eq!(parse_wordlist("foo\r\nbar\r\n"), ["foo", "bar"]);
+  }
+
+  #[test]
+  #[cfg(feature = "gzip")]
+  fn test_read_wordlist_gzip() {
+    let path = concat!(
+      env!("CARGO_MANIFEST_DIR"),
+      "/tests/fixtures/wordlist.txt.gz"
+    );
+    let wordlist = read_wordlist(path).unwrap();
+
+    assert_eq!(wordlist, ["11\tfoo", "12\tbar", "21\tbaz", "22\tqux"]);
+    assert!(Passphraser::new(4)
+      .wordlist(&wordlist)
+      .dice_count(2)
+      .dice_faces(2)
+      .validate()
+      .is_ok());
+  }
+
+  #[test]
+  fn test_wordlist_stats() {
+    let stats = wordlist_stats(&builtin_wordlist());
+
+    assert_eq!(stats.entries, 7776);
+    assert_eq!(stats.min_length, 3);
+    assert_eq!(stats.max_length, 9);
+    assert!((stats.average_length - 6.99).abs() < 0.01);
+    assert_eq!(stats.duplicates, 0);
+    assert!((stats.bits_per_word - 12.925).abs() < 0.001);
+
+    let plain = ["foo", "quux", "foo", "# comment", ""].map(String::from);
+    let stats = wordlist_stats(&plain);
+
+    assert_eq!(stats.entries, 3);
+    assert_eq!((stats.min_length, stats.max_length), (3, 4));
+    assert_eq!(stats.duplicates, 1);
+    assert_eq!(stats.bits_per_word, 1.0);
+    assert_eq!(wordlist_stats(&[]), WordlistStats::default());
+  }
+
+  #[test]
+  fn test_validate_wordlist() {
+    assert_eq!(validate_wordlist(&builtin_wordlist()), Ok(()));
+
+    let mut wordlist = builtin_wordlist();
+    wordlist.remove(0);
+    wordlist.push("66666 again".to_string());
+
+    let err = validate_wordlist(&wordlist).unwrap_err();
+
+    assert_eq!(err.missing, [11111]);
+    assert_eq!(err.duplicates, [66666]);
+
+    let wordlist = to_indices(4, 6)
+      .into_iter()
+      .map(|index| format!("{index} word"))
+      .collect::<Vec<_>>();
+
+    assert!(validate_wordlist(&wordlist).is_err());
+    assert_eq!(
+      Passphraser::new(6)
+        .wordlist(&wordlist)
+        .dice_count(4)
+        .validate(),
+      Ok(())
+    );
+  }
+
+  #[test]
+  fn test_builtin_wordlist_size() {
+    assert_eq!(BUILTIN_WORDLIST_SIZE, 7776);
+    assert_eq!(BUILTIN_WORDLIST_SIZE, builtin_wordlist_ref().len());
+    assert_eq!(count_lines("foo\nbar"), "foo\nbar".lines().count());
+    assert_eq!(count_lines(""), 0);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_generate_batch_into() {
+    let mut builder = Passphraser::new(4);
+    builder.numbers(2);
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut out = Vec::new();
+
+    builder.generate_batch_into(&mut out, 3, &mut rng);
+    builder.generate_batch_into(&mut out, 2, &mut rng);
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let expected = (0..5)
+      .map(|_| builder.generate_with(&mut rng).format())
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      out.iter().map(Passphrase::format).collect::<Vec<_>>(),
+      expected
+    );
+
+    let origins = out
+      .iter()
+      .map(|passphrase| {
+        passphrase
+          .origin
+          .as_ref()
+          .map(|Origin(builder)| Arc::as_ptr(builder))
+      })
+      .collect::<Vec<_>>();
+
+    assert!(origins[0].is_some());
+    assert!(origins[..3].iter().all(|origin| *origin == origins[0]));
+    assert_ne!(origins[3], origins[0]);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_generate_many() {
+    let wordlist = vec!["foo".to_string(), "bar".to_string()];
+
+    let mut builder = Passphraser::new(2);
+    builder.wordlist(&wordlist);
+
+    let passphrases = builder.generate_many(4).unwrap();
+    let formatted = passphrases
+      .iter()
+      .map(Passphrase::format)
+      .collect::<HashSet<_>>();
+
+    assert_eq!(formatted.len(), 4);
+    assert_eq!(
+      builder.generate_many(5).unwrap_err(),
+      GenerateError::TooFewCandidates {
+        requested: 5,
+        generated: 4
+      }
+    );
+    assert!(builder.generate_many(0).unwrap().is_empty());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_generation_stats() {
+    let builder = Passphraser::new(6);
+    let (passphrases, stats) = builder.generate_many_timed(10).unwrap();
+
+    assert_eq!(stats.count, 10);
+    assert_eq!(stats.count, passphrases.len());
+    assert!((stats.total_bits - calc_entropy(7776, 6) as f64 * 10.0).abs() < 1e-3);
+
+    let stats = GenerationStats::new(&passphrases[..4], Duration::from_millis(500));
+
+    assert_eq!(stats.count, 4);
+    assert_eq!(stats.passphrases_per_second(), 8.0);
+    assert!((stats.bits_per_second() - calc_entropy(7776, 6) as f64 * 8.0).abs() < 1e-3);
+    assert_eq!(GenerationStats::default().total_bits, 0.0);
+  }
+
+  #[test]
+  fn test_calc_entropy_no_replacement() {
+    assert_eq!(calc_entropy(4, 3), 6.0);
+    assert_eq!(calc_entropy_no_replacement(4, 3), 24f32.log2());
+    assert_eq!(calc_entropy_no_replacement(4, 1), calc_entropy(4, 1));
+    assert_eq!(calc_entropy_no_replacement(4, 6), 24f32.log2());
+    assert_eq!(calc_entropy_no_replacement(4, 0), 0.0);
+    assert!(calc_entropy_no_replacement(7776, 6) < calc_entropy(7776, 6));
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_prefer_short() {
+    let wordlist = parse_wordlist("ab\nabcdefgh\nab");
+    let mut builder = Passphraser::new(1000);
+    builder.wordlist(&wordlist).prefer_short(true).seed(42);
+
+    let passphrase = builder.generate();
+    let short = passphrase
+      .words()
+      .iter()
+      .filter(|word| *word == "ab")
+      .count();
+
+    // Weights are 1/2 and 1/8, so the short word is picked with probability 0.8.
+    assert!(
+      (750..850).contains(&short),
+      "picked short word {short} times"
+    );
+
+    let per_word = -(0.8f32 * 0.8f32.log2() + 0.2 * 0.2f32.log2());
+
+    assert_eq!(passphrase.entropy().possibilities, 2);
+    assert!((passphrase.entropy().entropy - per_word * 1000.0).abs() < 0.1);
+
+    builder.min_entropy(10.0);
+
+    assert_eq!(builder.generate().words().len(), 14);
+
+    let mut builder = Passphraser::new(6);
+    builder.prefer_short(true);
+
+    assert!(builder.generate().entropy().entropy < calc_entropy(7776, 6));
+    assert_eq!(builder.expected_entropy().possibilities, 7776);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_unique_words() {
+    let wordlist = parse_wordlist("foo\nbar\nbaz\nqux\nquux");
+    let mut builder = Passphraser::new(5);
+    builder.wordlist(&wordlist).unique_words(true).seed(42);
+
+    let passphrase = builder.generate_checked().unwrap();
+    let words = passphrase.words().iter().collect::<HashSet<_>>();
+
+    assert_eq!(words.len(), 5);
+    let expected = (1..=5).map(|n| calc_entropy(n, 1)).sum::<f32>();
+    assert!((passphrase.entropy().entropy - expected).abs() < 1e-4);
+
+    builder.length(6);
+
+    assert_eq!(
+      builder.generate_checked().unwrap_err(),
+      GenerateError::TooFewWords {
+        length: 6,
+        words: 5
+      }
+    );
+    assert_eq!(builder.generate().words().len(), 5);
+
+    let wordlist = ["11 foo", "12 foo", "21 bar", "22 bar", "33 baz"].map(String::from);
+    let mut builder = Passphraser::new(3);
+    builder
+      .wordlist(&wordlist)
+      .dice_count(2)
+      .dice_faces(2)
+      .unique_words(true);
+
+    assert_eq!(builder.expected_entropy().possibilities, 2);
+    assert_eq!(
+      builder.generate_checked().unwrap_err(),
+      GenerateError::TooFewWords {
+        length: 3,
+        words: 2
+      }
+    );
+    assert_eq!(builder.generate().words().len(), 2);
+
+    let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+    let wordlist = parse_wordlist("foo\nbar");
+    let mut builder = Passphraser::new(2);
+    builder.wordlist(&wordlist).unique_words(true);
+
+    assert_eq!(
+      builder.generate_checked_with(&mut rng).unwrap_err(),
+      GenerateError::RerollsExhausted
+    );
+  }
+
+  #[test]
+  fn test_builtin_wordlist_hash() {
+    assert_eq!(
+      builtin_wordlist_hash(),
+      "addd35536511597a02fa0a9ff1e5284677b8883b83e986e43f15a3db996b903e"
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_zero_length() {
+    assert_eq!(
+      Passphraser::new(0).generate_checked().unwrap_err(),
+      GenerateError::ZeroLength
+    );
+
+    assert!(Passphraser::new(0).generate().words().is_empty());
+    assert!(Passphraser::new(0)
+      .min_entropy(20.0)
+      .generate_checked()
+      .is_ok());
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_wordlists() {
+    let first = parse_wordlist("11\tfoo\n12\tbar\n21\tbaz\n22\tqux");
+    let second = parse_wordlist("11\tone\n12\ttwo\n21\tsix\n22\tten");
+
+    let mut builder = Passphraser::new(4);
+    builder
+      .wordlists(&[&first, &second])
+      .dice_count(2)
+      .dice_faces(2);
+
+    assert_eq!(builder.validate(), Ok(()));
+    assert_eq!(builder.expected_entropy().entropy, 12.0);
+
+    let words = (0..20)
+      .flat_map(|_| builder.generate_checked().unwrap().words().to_vec())
+      .collect::<HashSet<_>>();
+
+    let from = |list: &[String]| {
+      list
+        .iter()
+        .filter(|line| words.contains(&line[3..]))
+        .count()
+    };
+
+    assert!(from(&first) > 0 && from(&second) > 0);
+    assert_eq!(from(&first) + from(&second), words.len());
+
+    let passphrase = builder.generate_checked().unwrap();
+    assert!(passphrase.rolls().iter().all(|roll| roll.len() == 3));
+
+    let rolls = builder.parse_rolls("211 1-1-2").unwrap();
+    let passphrase = builder.generate_from_rolls(rolls).unwrap();
+
+    assert_eq!(passphrase.words(), ["one", "bar"]);
+    assert_eq!(
+      builder.parse_rolls("311"),
+      Err(RollsError::OutOfRange {
+        position: 1,
+        group: "311".to_string(),
+        faces: 2
+      })
+    );
+    assert!(builder.parse_rolls("113").is_err());
+  }
+
+  #[test]
+  fn test_duplicate_words() {
+    assert!(duplicate_words(&builtin_wordlist()).is_empty());
+
+    let wordlist = [
+      "11111 foo",
+      "11112 bar",
+      "11113 foo",
+      "11114 baz",
+      "11115 bar",
+      "11116 foo",
+    ];
+    let duplicates = duplicate_words(&wordlist.map(String::from));
+
+    assert_eq!(
+      duplicates,
+      [
+        ("bar".to_string(), vec![11112, 11115]),
+        ("foo".to_string(), vec![11111, 11113, 11116])
+      ]
+    );
+
+    let wordlist = parse_wordlist("foo\nbar\n# comment\nfoo\nbaz");
+
+    assert_eq!(
+      duplicate_words(&wordlist),
+      [("foo".to_string(), vec![1, 3])]
+    );
+    assert!(duplicate_words(&parse_wordlist("foo\nbar")).is_empty());
+  }
+
+  #[test]
+  fn test_crack_time() {
+    assert_eq!(crack_time(11.0, 1024.0), Duration::from_secs(1));
+    assert_eq!(crack_time(1000.0, 1e12), Duration::MAX);
+  }
+
+  #[test]
+  fn test_format_duration() {
+    assert_eq!(
+      format_duration(Duration::from_millis(10)),
+      "less than a second"
+    );
+    assert_eq!(format_duration(Duration::from_secs(1)), "1 second");
+    assert_eq!(format_duration(Duration::from_secs(150)), "2 minutes");
+    assert_eq!(format_duration(Duration::from_secs(86400 * 3)), "3 days");
+    assert_eq!(
+      format_duration(Duration::from_secs(31_557_600 * 100)),
+      "1 century"
+    );
+    assert_eq!(format_duration(Duration::from_secs(86400 * 366)), "1 year");
+    assert_eq!(
+      format_duration(crack_time(calc_entropy(7776, 6), 1e12)),
+      "35 centuries"
+    );
+  }
+
+  #[test]
+  fn test_index_scheme_positional() {
+    let scheme = IndexScheme::Positional;
+
+    assert_eq!(scheme.index(&[1, 1, 1, 1, 1], 6), 0);
+    assert_eq!(scheme.index(&[1, 1, 1, 1, 2], 6), 1);
+    assert_eq!(scheme.index(&[1, 1, 1, 2, 1], 6), 6);
+    assert_eq!(scheme.index(&[6, 6, 6, 6, 6], 6), 7775);
+    assert_eq!(scheme.indices(5, 6), (0..7776).collect::<Vec<_>>());
+    assert_eq!(scheme.indices(4, 10), (0..10000).collect::<Vec<_>>());
+    assert_eq!(IndexScheme::Concatenated.indices(2, 3), to_indices(2, 3));
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_index_scheme_positional_wordlist() {
+    let wordlist = (0..1296)
+      .map(|index| format!("{index:04} word{index}"))
+      .collect::<Vec<_>>();
+
+    let mut builder = Passphraser::new(4);
+    builder
+      .wordlist(&wordlist)
+      .dice_count(4)
+      .index_scheme(IndexScheme::Positional);
+
+    assert!(builder.validate().is_ok());
+
+    let rolls = builder.parse_rolls("1111 1112 1121 6666").unwrap();
+    let passphrase = builder.generate_from_rolls(rolls).unwrap();
+
+    assert_eq!(passphrase.words(), ["word0", "word1", "word6", "word1295"]);
+    assert_eq!(passphrase.indices(), [0, 1, 6, 1295]);
+    assert_eq!(passphrase.entropy().possibilities, 1296);
+  }
+
+  #[test]
+  fn test_to_indices() {
+    assert_eq!(to_indices(2, 3), [11, 12, 13, 21, 22, 23, 31, 32, 33]);
+    assert_eq!(to_indices(5, 6).len(), 7776);
+  }
+
+  #[test]
+  fn test_format_delimiters() {
+    let passphrase = passphrase_of(&["foo", "bar", "baz"]);
+    let delimiters =
+      |delimiters: &[&str]| Preset::Delimiters(delimiters.iter().map(|d| d.to_string()).collect());
+
+    assert_eq!(
+      passphrase.format_with(&delimiters(&["-", "."])),
+      "foo-bar.baz"
+    );
+    assert_eq!(passphrase.format_with(&delimiters(&["_"])), "foo_bar_baz");
+    assert_eq!(passphrase.format_with(&delimiters(&[])), "foo bar baz");
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_format_custom_delimiter() {
+    let mut passphrase = passphrase_of(&["foo", "bar", "baz"]);
+    passphrase.delimiter = Some("-".to_string());
+
+    let cases = [
+      (Preset::PascalCase, "Foo-Bar-Baz"),
+      (Preset::CamelCase, "foo-Bar-Baz"),
+      (Preset::SnakeCase, "foo-bar-baz"),
+      (Preset::TitleCase, "Foo-Bar-Baz"),
+      (Preset::UpperCase, "FOO-BAR-BAZ"),
+      (Preset::CapitalizePositions(vec![1]), "foo-Bar-baz"),
+      (Preset::Delimiters(vec![".".to_string()]), "foo-bar-baz"),
+      (
+        Preset::Arbitrary {
+          capitalize: true,
+          delimiter: Some(".".to_string()),
+        },
+        "Foo-Bar-Baz",
+      ),
+      (Preset::Default, "foo-bar-baz"),
+    ];
+
+    for (preset, expected) in cases {
+      assert_eq!(passphrase.format_with(&preset), expected, "{preset:?}");
+    }
+
+    passphrase.delimiter = Some("x".to_string());
+    assert_eq!(passphrase.format_with(&Preset::UpperCase), "FOOxBARxBAZ");
+
+    let generated = Passphraser::new(3)
+      .preset(Preset::PascalCase)
+      .delimiter(".")
+      .generate();
+
+    assert_eq!(generated.format().split('.').count(), 3);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_reroll() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut builder = Passphraser::new(6);
+    builder.checksum(true);
+
+    let mut passphrase = builder.generate_with(&mut rng);
+    let words = passphrase.words().to_vec();
+    let rolls = passphrase.rolls().to_vec();
+    let entropy = passphrase.entropy().entropy;
+
+    passphrase.reroll(2, &mut rng).unwrap();
+
+    for (position, (before, after)) in words.iter().zip(passphrase.words()).enumerate().take(6) {
+      assert_eq!(before == after, position != 2);
+    }
+
+    assert_eq!(passphrase.rolls()[..2], rolls[..2]);
+    assert_eq!(passphrase.rolls()[3..], rolls[3..]);
+    assert_ne!(passphrase.rolls()[2], rolls[2]);
+    assert_eq!(passphrase.entropy().entropy, entropy);
+    assert_eq!(
+      builder.check_word(&passphrase.words()[..6]).as_deref(),
+      passphrase.check_word()
+    );
+    assert_eq!(
+      passphrase_of(&["foo"]).reroll(0, &mut rng),
+      Err(GenerateError::NoWordlist)
+    );
+
+    let mut passphrase = builder
+      .checksum(false)
+      .exclude(&["abacus".to_string()])
+      .generate_from_rolls_with(&mut rng, vec![vec![1; 5], vec![1, 1, 1, 1, 2]])
+      .unwrap();
+
+    assert_eq!(passphrase.words(), ["abacus", "abdomen"]);
+
+    passphrase.reroll(0, &mut rng).unwrap();
+
+    assert_ne!(passphrase.words()[0], "abacus");
+    assert!(passphrase.rolls().is_empty());
+    assert!(passphrase.indices().is_empty());
+  }
+
+  #[test]
+  fn test_phonetic() {
+    let mut passphrase = passphrase_of(&["ox", "Yak"]);
+    passphrase.numbers = "7".to_string();
+    passphrase.preset = Preset::KebabCase;
+
+    assert_eq!(
+      passphrase.phonetic(),
+      "oscar x-ray dash capital yankee alfa kilo seven"
+    );
+  }
+
+  #[test]
+  fn test_format_opts() {
+    let mut passphrase = passphrase_of(&["foo", "bAr", "baz"]);
+    passphrase.numbers = "42".to_string();
+
+    let options = FormatOptions {
+      delimiter: "+".to_string(),
+      capitalize: true,
+      case: Case::Lower,
+      ..FormatOptions::default()
+    };
+
+    assert_eq!(passphrase.format_opts(&options), "Foo+Bar+Baz42");
+
+    let options = FormatOptions {
+      delimiter: String::new(),
+      capitalize: false,
+      case: Case::Camel,
+      ..FormatOptions::default()
+    };
+
+    assert_eq!(passphrase.format_opts(&options), "fooBArBaz42");
+
+    let options = FormatOptions {
+      case: Case::Upper,
+      ..FormatOptions::default()
+    };
+
+    assert_eq!(passphrase.format_opts(&options), "FOOBARBAZ42");
+    assert_eq!(
+      passphrase.format_opts(&Preset::KebabCase.format_options().unwrap()),
+      passphrase.format_with(&Preset::KebabCase)
+    );
+    assert_eq!(Preset::RandomCase.format_options(), None);
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_prefix_suffix() {
+    let passphrase = Passphraser::new(3)
+      .preset(Preset::KebabCase)
+      .numbers(2)
+      .prefix("x-")
+      .suffix("!2024")
+      .generate();
+
+    let formatted = passphrase.format();
+    let inner = formatted
+      .strip_prefix("x-")
+      .and_then(|formatted| formatted.strip_suffix("!2024"))
+      .unwrap();
+
+    assert_eq!(inner.split('-').count(), 3);
+    assert!(inner.starts_with(passphrase.words()[0].as_str()));
+    assert!(inner[inner.len() - 2..].chars().all(|c| c.is_ascii_digit()));
+    assert_eq!(
+      passphrase.entropy().entropy,
+      calc_entropy(7776, 3) + calc_entropy(10, 2)
+    );
+
+    let options = FormatOptions {
+      delimiter: ".".to_string(),
+      prefix: "<".to_string(),
+      suffix: ">".to_string(),
+      ..FormatOptions::default()
+    };
+
+    assert_eq!(
+      passphrase_of(&["foo", "bar"]).format_opts(&options),
+      "<foo.bar>"
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_random_delimiters() {
+    let delimiters = ["-", "_", ".", "-"].map(String::from);
+
+    let mut builder = Passphraser::new(6);
+    builder.seed(42).random_delimiters(&delimiters);
+
+    let passphrase = builder.generate();
+    let formatted = passphrase.format();
+
+    assert_eq!(formatted, builder.generate().format());
+    assert_eq!(passphrase.delimiters.len(), 5);
+
+    for (position, delimiter) in passphrase.delimiters.iter().flatten().enumerate() {
+      assert!(["-", "_", "."].contains(&delimiter.as_str()));
+      assert!(formatted.contains(&format!("{}{delimiter}", passphrase.words()[position])));
+    }
+
+    assert!(!formatted.contains(' '));
+    assert_eq!(
+      passphrase.entropy().entropy,
+      calc_entropy(7776, 6) + calc_entropy(3, 5)
+    );
+
+    builder.group_size(3);
+
+    let passphrase = builder.generate();
+
+    assert_eq!(passphrase.format().split(' ').count(), 2);
+    assert_eq!(
+      passphrase.entropy().entropy,
+      calc_entropy(7776, 6) + calc_entropy(3, 4)
+    );
+  }
+
+  #[test]
+  fn test_format_all() {
+    let formats = passphrase_of(&["foo", "bar"]).format_all();
+    let names = formats
+      .iter()
+      .map(|(name, _)| name.as_str())
+      .collect::<Vec<_>>();
+
+    assert_eq!(names, Preset::NAMES);
+    assert!(formats.contains(&("pascal".to_string(), "FooBar".to_string())));
+    assert!(formats.contains(&("kebab".to_string(), "foo-bar".to_string())));
+    assert!(formats.contains(&("snake".to_string(), "foo_bar".to_string())));
+    assert!(formats.contains(&("title".to_string(), "Foo Bar".to_string())));
+  }
+
+  #[test]
+  #[cfg(feature = "thread-rng")]
+  fn test_format_groups() {
+    let mut passphrase = passphrase_of(&["abc", "def", "ghi", "jkl"]);
+    passphrase.delimiter = Some("-".to_string());
+    passphrase.group_size = 2;
+
+    assert_eq!(passphrase.format(), "abc-def ghi-jkl");
+    assert_eq!(
+      passphrase.format_with(&Preset::PascalCase),
+      "Abc-Def Ghi-Jkl"
+    );
+
+    passphrase.group_delimiter = Some(" / ".to_string());
+    passphrase.group_size = 3;
+
+    assert_eq!(passphrase.format(), "abc-def-ghi / jkl");
+
+    let generated = Passphraser::new(4)
+      .preset(Preset::KebabCase)
+      .group_size(2)
+      .group_delimiter(".")
+      .generate()
+      .format();
+
+    assert_eq!(generated.split('.').count(), 2);
+    assert!(generated
+      .split('.')
+      .all(|group| group.split('-').count() == 2));
+  }
+
+  #[test]
+  fn test_to_pair() {
+    let pair = |line: &str| to_pair(to_components(line));
+    let expected = Some((11111, "abacus".to_string()));
+
+    assert_eq!(pair("11111 abacus"), expected);
+    assert_eq!(pair("11111\tabacus"), expected);
+    assert_eq!(pair("11111:abacus"), expected);
+    assert_eq!(pair("11111 : abacus"), expected);
+    assert_eq!(pair("abacus"), None);
+    assert_eq!(pair("abacus:11111"), None);
+
+    let wordlist = builtin_wordlist()
+      .iter()
+      .map(|line| line.replace('\t', ":"))
+      .collect::<Vec<_>>();
+
+    assert_eq!(validate_wordlist(&wordlist), Ok(()));
   }
 
   #[test]
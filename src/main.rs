@@ -1,76 +1,699 @@
 mod cli;
+mod error;
 
-use std::fs::File;
-use std::io::{BufRead, BufReader, Result};
-use std::path::Path;
+use std::fs;
+#[cfg(feature = "interactive")]
+use std::io::IsTerminal;
+use std::io::{self, Result, Write};
 use std::process;
+use std::time::Instant;
 
-use clap::Parser;
-use cli::Cli;
+use arboard::Clipboard;
+use clap::error::ErrorKind;
+use clap::CommandFactory;
+use cli::{Cli, EntropyUnit, RngSource};
 use colored::*;
-use diceware::{Passphraser, Preset};
+use diceware::{
+  builtin_wordlist_hash, concat_wordlists, crack_time, format_duration, parse_wordlist,
+  GenerationStats, Passphrase, Passphraser, Policy, Preset, Strength, Template,
+  BUILTIN_WORDLIST_SIZE,
+};
+use error::AppError;
+use rand::rngs::{OsRng, StdRng};
+use rand::{RngCore, SeedableRng};
+
+/// Path that makes the wordlist to be read from stdin.
+const STDIN: &str = "-";
 
 fn main() {
-  let cli = Cli::parse();
+  let cli = Cli::parse_with_env();
+
+  if let Err(err) = run(cli) {
+    eprintln!("{err}");
+    process::exit(err.exit_code());
+  }
+}
+
+/// Runs the CLI with parsed arguments.
+fn run(cli: Cli) -> std::result::Result<(), AppError> {
+  // Printing completions doesn't need a passphrase, so exit right away.
+  if let Some(shell) = cli.completions {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+    return Ok(());
+  }
+
+  // Printing the built-in wordlist identity doesn't need a passphrase either.
+  if cli.version_wordlist {
+    println!("Wordlist: EFF long wordlist");
+    println!("Words: {BUILTIN_WORDLIST_SIZE}");
+    println!("SHA-256: {}", builtin_wordlist_hash());
+    return Ok(());
+  }
+
+  // Colors are already disabled if `NO_COLOR` is set or stdout is not a tty, so only the explicit
+  // flags need handling.
+  if cli.no_color || cli.quiet {
+    colored::control::set_override(false);
+  }
+
   let mut builder = Passphraser::new(cli.length);
 
-  // Trying to load custom wordlist if set.
-  if let Some(path) = cli.wordlist {
-    if let Ok(wordlist) = read_wordlist(path) {
-      builder.wordlist(&wordlist);
-    } else {
-      println!("Couldn't read the wordlist. Make sure the file exists.");
-      process::exit(1);
+  if let Some(bits) = cli.min_entropy {
+    builder.min_entropy(bits);
+  }
+
+  if let Some(chars) = cli.min_chars {
+    builder.min_chars(chars);
+  }
+
+  if cli.policy.is_some() {
+    builder.policy(Policy::STRICT);
+  }
+
+  // Trying to load custom wordlists if set, concatenating them into one.
+  if !cli.wordlist.is_empty() {
+    let mut wordlists = Vec::new();
+
+    for path in &cli.wordlist {
+      match read_wordlist(path) {
+        | Ok(wordlist) if wordlist.is_empty() => return Err(AppError::WordlistEmpty),
+        | Ok(wordlist) => wordlists.push(wordlist),
+        | Err(_) if path == STDIN => return Err(AppError::WordlistStdin),
+        | Err(_) => return Err(AppError::WordlistMissing),
+      };
     }
+
+    builder.wordlist_owned(concat_wordlists(&wordlists)?);
+  }
+
+  // Checking the wordlist for duplicate words doesn't need a passphrase.
+  if cli.check_duplicates {
+    return check_duplicates(&builder);
+  }
+
+  // Neither does printing wordlist stats.
+  if cli.show_wordlist_stats {
+    print_wordlist_stats(&builder);
+    return Ok(());
+  }
+
+  // Making sure that every index of a custom wordlist can be resolved to a word.
+  if !cli.wordlist.is_empty() {
+    builder.validate()?;
   }
 
   // Setting a preset for formatting.
-  let mut preset = if let Some(preset) = cli.preset {
-    Preset::from(&preset)
+  let mut preset = cli.preset.clone().unwrap_or_default();
+
+  if cli.random_case {
+    preset = Preset::RandomCase
+  }
+
+  if let Some(delimiter) = &cli.delimiter {
+    builder.delimiter(delimiter);
+  }
+
+  if let Some(group_size) = cli.group_size {
+    builder.group_size(group_size);
+  }
+
+  if let Some(delimiter) = &cli.group_delimiter {
+    builder.group_delimiter(delimiter);
+  }
+
+  if let Some(chars) = &cli.random_delim {
+    let delimiters = chars.chars().map(String::from).collect::<Vec<_>>();
+    builder.random_delimiters(&delimiters);
+  }
+
+  if let Some(prefix) = &cli.prefix {
+    builder.prefix(prefix);
+  }
+
+  if let Some(suffix) = &cli.suffix {
+    builder.suffix(suffix);
+  }
+
+  if let Some(template) = &cli.template {
+    preset = Preset::Template(Template::new(template));
+  }
+
+  builder
+    .preset(preset)
+    .capitalize(cli.capitalize)
+    .normalize_case(cli.normalize_case)
+    .checksum(cli.checksum)
+    .shuffle(cli.shuffle)
+    .unique_words(cli.unique_words)
+    .numbers(cli.numbers)
+    .exclude(&cli.exclude);
+
+  if let Some(path) = &cli.banlist {
+    let banlist = diceware::read_wordlist(path).map_err(|_| AppError::BanlistMissing)?;
+    builder.banlist(&banlist);
+  }
+
+  if let Some(symbols) = &cli.symbol {
+    builder.symbols(symbols);
+  }
+
+  // Making sure that the minimum entropy doesn't need more words than the length can be set to.
+  cli
+    .check_min_entropy(&builder)
+    .unwrap_or_else(|err| err.exit());
+
+  // Picking the source of randomness.
+  let mut rng: Box<dyn RngCore> = match (cli.rng, cli.seed) {
+    | (RngSource::Os, None) => Box::new(OsRng),
+    | (RngSource::Thread, None) => Box::new(rand::thread_rng()),
+    | (RngSource::Seed, Some(seed)) => Box::new(StdRng::seed_from_u64(seed)),
+    | (RngSource::Seed, None) => {
+      Cli::command()
+        .error(
+          ErrorKind::MissingRequiredArgument,
+          "`--rng seed` requires `--seed <SEED>`",
+        )
+        .exit()
+    },
+    | (_, Some(_)) => {
+      Cli::command()
+        .error(
+          ErrorKind::ArgumentConflict,
+          "`--seed` can only be used with `--rng seed`",
+        )
+        .exit()
+    },
+  };
+
+  // Printing passphrases as JSON skips the rest of the output.
+  if cli.json || cli.jsonl {
+    return print_json(
+      &builder,
+      &mut *rng,
+      cli.count,
+      cli.rolls.as_deref(),
+      cli.jsonl,
+      cli.unique_candidates,
+      cli.show_all_formats,
+    );
+  }
+
+  // Letting the user pick the passphrase if requested, printing only the picked one.
+  #[cfg(feature = "interactive")]
+  if cli.interactive && io::stdin().is_terminal() {
+    if let Some(passphrase) = pick_interactively(&builder, &mut *rng)? {
+      println!("{}", passphrase.format());
+      return Ok(());
+    }
+  }
+
+  // Generate the passphrase along with the rest of candidates, timing it for stats.
+  let started = Instant::now();
+
+  let passphrases = if cli.unique_candidates {
+    generate_distinct(&builder, &mut *rng, cli.count)?
   } else {
-    Preset::Default
+    let mut passphrases = vec![generate(&builder, &mut *rng, cli.rolls.as_deref())?];
+
+    for _ in 1..cli.count {
+      passphrases.push(generate(&builder, &mut *rng, None)?);
+    }
+
+    passphrases
+  };
+
+  let stats = GenerationStats::new(&passphrases, started.elapsed());
+
+  let Some((passphrase, candidates)) = passphrases.split_first() else {
+    return Err(AppError::EmptyPassphrase);
   };
 
-  if cli.capitalize {
-    preset = Preset::Arbitrary {
-      capitalize: cli.capitalize,
-      delimiter: None,
+  // Writing passphrases to the file if requested, or printing them otherwise.
+  if let Some(path) = &cli.output {
+    write_output(path, &passphrases, &cli).map_err(|_| AppError::OutputUnwritable)?;
+  } else {
+    #[cfg(feature = "qr")]
+    let qr_only = cli.qr_only;
+    #[cfg(not(feature = "qr"))]
+    let qr_only = false;
+
+    if !qr_only {
+      print_passphrase(passphrase, &cli);
+    }
+
+    #[cfg(feature = "qr")]
+    if cli.qr_only || (cli.qr && !cli.quiet) {
+      if !cli.qr_only {
+        println!();
+      }
+
+      print_qr(&passphrase.format());
+    }
+
+    for candidate in candidates {
+      print_passphrase(candidate, &cli);
+    }
+  }
+
+  if cli.copy {
+    if let Err(err) = copy_to_clipboard(&passphrase.format()) {
+      eprintln!("\nCouldn't copy the passphrase to the clipboard: {err}.");
     }
   }
 
-  if cli.delimiter.is_some() {
-    preset = Preset::Arbitrary {
-      capitalize: cli.capitalize,
-      delimiter: cli.delimiter,
+  if cli.entropy && !cli.quiet && cli.output.is_none() {
+    let entropy = passphrase.entropy();
+
+    let crack_time = crack_time(entropy.entropy, cli.guess_rate);
+
+    let possibilities = format!("{}", entropy.possibilities).blue();
+    let format_bits = |bits: f32| format_entropy(bits, entropy.bits_per_word(), &cli).blue();
+    let bits_per_word = format_bits(entropy.bits_per_word());
+    let target = entropy.target.map(format_bits);
+    let overshoot = entropy.overshoot().map(format_bits);
+    let entropy = format_bits(entropy.entropy);
+    let crack_time = format_duration(crack_time).blue();
+
+    let strength = passphrase.strength();
+    let label = strength.to_string();
+
+    let strength = match strength {
+      | Strength::Weak => label.red(),
+      | Strength::Fair => label.yellow(),
+      | Strength::Strong => label.green(),
+      | Strength::Excellent => label.bright_green(),
+    };
+
+    println!("\nPossibilities: {possibilities}");
+    println!("Entropy: {entropy}");
+    println!("Entropy per word: {bits_per_word}");
+
+    if let (Some(target), Some(overshoot)) = (target, overshoot) {
+      println!("Target: {target} (overshoot: {overshoot})");
     }
+
+    println!("Strength: {strength}");
+    println!(
+      "Memorability: {}",
+      format!("{:.2}", passphrase.memorability()).blue()
+    );
+    println!(
+      "Time to crack: {crack_time} (at {:e} guesses/s)",
+      cli.guess_rate
+    );
+
+    if !cli.no_url {
+      println!("\nMore about entropy at https://theworld.com/~reinhold/dicewarefaq.html#entropy");
+    }
+  }
+
+  if cli.stats {
+    print_generation_stats(&stats, passphrase.entropy().bits_per_word(), &cli);
+  }
+
+  Ok(())
+}
+
+/// Prints how long generation took, along with the total entropy and the rate it was generated at.
+/// Entropy per word is used to show entropy in words.
+fn print_generation_stats(stats: &GenerationStats, bits_per_word: f32, cli: &Cli) {
+  let format_bits = |bits: f64| format_entropy(bits as f32, bits_per_word, cli).blue();
+
+  println!("\nGenerated: {}", format!("{}", stats.count).blue());
+  println!("Time: {}", format!("{:.2?}", stats.duration).blue());
+  println!("Total entropy: {}", format_bits(stats.total_bits));
+  println!("Rate: {}/s", format_bits(stats.bits_per_second()));
+}
+
+/// Generates the given number of distinct passphrases using the given RNG.
+fn generate_distinct(
+  builder: &Passphraser,
+  rng: &mut dyn RngCore,
+  count: usize,
+) -> std::result::Result<Vec<Passphrase>, AppError> {
+  let passphrases = builder.generate_many_with(count, rng)?;
+
+  if passphrases
+    .iter()
+    .any(|passphrase| passphrase.words().is_empty())
+  {
+    return Err(AppError::EmptyPassphrase);
+  }
+
+  Ok(passphrases)
+}
+
+/// Formats entropy given in bits using the unit and number of decimal places set in the CLI. Words
+/// are counted using the given entropy per word of the wordlist in use.
+fn format_entropy(bits: f32, bits_per_word: f32, cli: &Cli) -> String {
+  let precision = cli.entropy_precision;
+
+  match cli.entropy_unit {
+    | EntropyUnit::Bits => format!("{bits:.precision$} bits"),
+    | EntropyUnit::Nats => format!("{:.precision$} nats", bits * std::f32::consts::LN_2),
+    | EntropyUnit::Words => {
+      let words = if bits_per_word > 0.0 {
+        bits / bits_per_word
+      } else {
+        0.0
+      };
+
+      format!("{words:.precision$} words")
+    },
   }
+}
 
-  // Generate the passphrase.
-  let passphrase = builder.preset(preset).generate();
+/// Generates a passphrase, either from the given dice rolls or randomly, using the given RNG.
+fn generate(
+  builder: &Passphraser,
+  rng: &mut dyn RngCore,
+  rolls: Option<&str>,
+) -> std::result::Result<Passphrase, AppError> {
+  let passphrase = match rolls {
+    | Some(rolls) => builder.generate_from_rolls_with(rng, builder.parse_rolls(rolls)?)?,
+    | None => builder.generate_checked_with(rng)?,
+  };
 
   if passphrase.words().is_empty() {
-    println!("Couldn't generate a passphrase with given parameters.");
-    process::exit(1);
+    return Err(AppError::EmptyPassphrase);
+  }
+
+  Ok(passphrase)
+}
+
+/// Prints passphrases as a JSON array, or as JSON Lines if `lines` is set, flushing after each line
+/// so that they can be streamed. Streaming stops silently once stdout is closed. If `formats` is
+/// set, each passphrase also has a map of every preset to the passphrase formatted with it.
+fn print_json(
+  builder: &Passphraser,
+  rng: &mut dyn RngCore,
+  count: usize,
+  rolls: Option<&str>,
+  lines: bool,
+  unique: bool,
+  formats: bool,
+) -> std::result::Result<(), AppError> {
+  let mut stdout = io::stdout().lock();
+  let mut passphrases = Vec::new();
+
+  let mut distinct = if unique {
+    generate_distinct(builder, rng, count)?.into_iter()
   } else {
-    println!("{}", &passphrase.format().green().bold());
+    Vec::new().into_iter()
+  };
 
-    if cli.entropy {
-      let entropy = passphrase.entropy();
+  for _ in 0..count {
+    let passphrase = match distinct.next() {
+      | Some(passphrase) => passphrase,
+      | None => generate(builder, rng, rolls)?,
+    };
 
-      let possibilities = format!("{}", entropy.possibilities).blue();
-      let entropy = format!("{:.2} bits", entropy.entropy).blue();
+    if lines {
+      let written = if formats {
+        write_json_line(&mut stdout, &passphrase.with_formats())
+      } else {
+        write_json_line(&mut stdout, &passphrase)
+      };
 
-      println!("\nPossibilities: {possibilities}");
-      println!("Entropy: {entropy}");
-      println!("\nMore about entropy at https://theworld.com/~reinhold/dicewarefaq.html#entropy");
+      match written {
+        | Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+        | result => result.map_err(|_| AppError::OutputUnwritable)?,
+      }
+    } else {
+      passphrases.push(passphrase);
+    }
+  }
+
+  if !lines {
+    let written = if formats {
+      let passphrases = passphrases
+        .iter()
+        .map(Passphrase::with_formats)
+        .collect::<Vec<_>>();
+      serde_json::to_writer_pretty(&mut stdout, &passphrases)
+    } else {
+      serde_json::to_writer_pretty(&mut stdout, &passphrases)
+    };
+
+    written.map_err(|_| AppError::OutputUnwritable)?;
+    writeln!(stdout).map_err(|_| AppError::OutputUnwritable)?;
+  }
+
+  Ok(())
+}
+
+/// Writes the passphrase as a single line of JSON and flushes it.
+fn write_json_line(writer: &mut impl Write, passphrase: &impl serde::Serialize) -> Result<()> {
+  serde_json::to_writer(&mut *writer, passphrase)?;
+  writeln!(writer)?;
+  writer.flush()
+}
+
+/// Reads a wordlist with `<index> <word>` pairs from the given path, or from stdin if the path is
+/// `-`, and returns a [Result] with vector of lines.
+fn read_wordlist(path: &str) -> Result<Vec<String>> {
+  if path == STDIN {
+    Ok(parse_wordlist(&io::read_to_string(io::stdin())?))
+  } else {
+    diceware::read_wordlist(path)
+  }
+}
+
+/// Shows passphrases on stderr until one is accepted, generating a new one on space, or re-rolling
+/// the word at the pressed digit, keeping the rest. Returns [None] if the terminal can't be
+/// switched to raw mode, so the caller can fall back to generating a passphrase non-interactively.
+#[cfg(feature = "interactive")]
+fn pick_interactively(
+  builder: &Passphraser,
+  rng: &mut dyn RngCore,
+) -> std::result::Result<Option<Passphrase>, AppError> {
+  use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+  use crossterm::terminal::{self, Clear, ClearType};
+
+  if terminal::enable_raw_mode().is_err() {
+    return Ok(None);
+  }
+
+  eprint!("Press space for another passphrase, 1-9 to re-roll a word, enter to accept it, or escape to cancel.\r\n");
+
+  let mut passphrase = generate(builder, rng, None);
+
+  let picked = loop {
+    match &passphrase {
+      | Ok(candidate) => {
+        let _ = crossterm::execute!(io::stderr(), Clear(ClearType::CurrentLine));
+        eprint!("\r{}", candidate.format().green().bold());
+      },
+      | Err(_) => break passphrase.map(Some),
+    }
+
+    match event::read() {
+      | Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+        match key.code {
+          | KeyCode::Char(' ') => passphrase = generate(builder, rng, None),
+          | KeyCode::Char(c @ '1'..='9') => {
+            if let Ok(candidate) = &mut passphrase {
+              let position = c as usize - '1' as usize;
+
+              if position < candidate.words().len() - usize::from(candidate.check_word().is_some())
+              {
+                let _ = candidate.reroll(position, rng);
+              }
+            }
+          },
+          | KeyCode::Enter => break passphrase.map(Some),
+          | KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            break Err(AppError::Cancelled)
+          },
+          | KeyCode::Esc | KeyCode::Char('q') => break Err(AppError::Cancelled),
+          | _ => {},
+        }
+      },
+      | Ok(_) => {},
+      | Err(_) => break Err(AppError::Cancelled),
     }
+  };
+
+  let _ = terminal::disable_raw_mode();
+  eprintln!();
+
+  picked
+}
+
+/// Prints the passphrase, followed by its acronym, phonetic spelling, formats and rolls if
+/// requested.
+fn print_passphrase(passphrase: &Passphrase, cli: &Cli) {
+  println!("{}", &passphrase.format().green().bold());
+
+  if cli.acronym {
+    println!("{}", passphrase.acronym().dimmed());
+  }
+
+  if cli.phonetic {
+    println!("{}", passphrase.phonetic().dimmed());
+  }
+
+  if cli.show_all_formats {
+    print_formats(passphrase);
+  }
+
+  if cli.show_rolls {
+    print_rolls(passphrase);
+  }
+}
+
+/// Prints the passphrase formatted with every preset, aligning formats by preset names.
+fn print_formats(passphrase: &Passphrase) {
+  println!();
+
+  for (name, formatted) in passphrase.format_all() {
+    println!("{:<8}{}", format!("{name}:").blue(), formatted);
+  }
+}
+
+/// Prints each word of the passphrase along with its dice roll, if it has one.
+fn print_rolls(passphrase: &Passphrase) {
+  println!();
+
+  for (position, word) in passphrase.words().iter().enumerate() {
+    let roll = passphrase
+      .rolls()
+      .get(position)
+      .map(|roll| roll.iter().map(usize::to_string).collect::<String>())
+      .unwrap_or_else(|| "-".to_string());
+
+    println!("{} {}", roll.blue(), word);
+  }
+}
+
+/// Writes passphrases to the file at the given path, one per line, followed by their entropy as
+/// comments if it's shown, using the unit and precision set in the CLI. Passphrases are generated
+/// with the same configuration, so entropy is written once.
+fn write_output(path: &str, passphrases: &[Passphrase], cli: &Cli) -> Result<()> {
+  let mut file = fs::File::create(path)?;
+
+  for passphrase in passphrases {
+    writeln!(file, "{}", passphrase.format())?;
+  }
+
+  if let (true, Some(passphrase)) = (cli.entropy, passphrases.first()) {
+    let guess_rate = cli.guess_rate;
+    let entropy = passphrase.entropy();
+    let crack_time = format_duration(crack_time(entropy.entropy, guess_rate));
+    let format_bits = |bits: f32| format_entropy(bits, entropy.bits_per_word(), cli);
+
+    writeln!(file, "# Possibilities: {}", entropy.possibilities)?;
+    writeln!(file, "# Entropy: {}", format_bits(entropy.entropy))?;
+    writeln!(
+      file,
+      "# Entropy per word: {}",
+      format_bits(entropy.bits_per_word())
+    )?;
+    writeln!(file, "# Strength: {}", passphrase.strength())?;
+    writeln!(
+      file,
+      "# Time to crack: {crack_time} (at {guess_rate:e} guesses/s)"
+    )?;
   }
+
+  Ok(())
+}
+
+/// Prints duplicate words in the wordlist along with their indices, returning an error if there are
+/// any duplicates.
+fn check_duplicates(builder: &Passphraser) -> std::result::Result<(), AppError> {
+  let duplicates = builder.duplicate_words();
+
+  if duplicates.is_empty() {
+    println!("No duplicate words found.");
+    return Ok(());
+  }
+
+  for (word, indices) in &duplicates {
+    let indices = indices
+      .iter()
+      .map(usize::to_string)
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    println!("{}: {}", word.yellow(), indices);
+  }
+
+  Err(AppError::DuplicateWords(duplicates.len()))
+}
+
+/// Prints statistics of the wordlist.
+fn print_wordlist_stats(builder: &Passphraser) {
+  let stats = builder.wordlist_stats();
+
+  println!("Entries: {}", format!("{}", stats.entries).blue());
+  println!(
+    "Word length: {} to {}, {} on average",
+    format!("{}", stats.min_length).blue(),
+    format!("{}", stats.max_length).blue(),
+    format!("{:.2}", stats.average_length).blue()
+  );
+  println!("Duplicates: {}", format!("{}", stats.duplicates).blue());
+  println!(
+    "Entropy per word: {}",
+    format!("{:.2} bits", stats.bits_per_word).blue()
+  );
 }
 
-/// Reads a wordlist with `<index> <word>` pairs and returns a [Result] with vector of lines.
-fn read_wordlist<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
-  let file = File::open(path)?;
-  let reader = BufReader::new(file);
+/// Prints given text as a QR code, using Unicode half blocks if the locale is UTF-8, or falling
+/// back to ASCII chars otherwise. Colors are inverted to suit dark terminals, which scanners handle
+/// fine.
+#[cfg(feature = "qr")]
+fn print_qr(text: &str) {
+  use qrcode::render::unicode::Dense1x2;
+  use qrcode::QrCode;
+
+  let code = match QrCode::new(text) {
+    | Ok(code) => code,
+    | Err(err) => {
+      eprintln!("\nCouldn't render the QR code: {err}.");
+      return;
+    },
+  };
+
+  let rendered = if supports_blocks() {
+    code
+      .render::<Dense1x2>()
+      .dark_color(Dense1x2::Light)
+      .light_color(Dense1x2::Dark)
+      .build()
+  } else {
+    code
+      .render::<char>()
+      .dark_color(' ')
+      .light_color('#')
+      .module_dimensions(2, 1)
+      .build()
+  };
+
+  println!("{rendered}");
+}
+
+/// Returns whether the terminal is likely to render Unicode block chars, judging by the locale.
+#[cfg(feature = "qr")]
+fn supports_blocks() -> bool {
+  if cfg!(windows) {
+    return true;
+  }
+
+  ["LC_ALL", "LC_CTYPE", "LANG"]
+    .iter()
+    .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+    .is_some_and(|locale| {
+      let locale = locale.to_lowercase();
+      locale.contains("utf-8") || locale.contains("utf8")
+    })
+}
 
-  reader.lines().collect()
+/// Places given text into the system clipboard.
+fn copy_to_clipboard(text: &str) -> std::result::Result<(), arboard::Error> {
+  Clipboard::new()?.set_text(text)
 }